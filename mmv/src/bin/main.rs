@@ -1,38 +1,298 @@
 extern crate clap;
 
-use clap::Parser;
-use mass_move_lib::mass_move::mass_move;
+use clap::{Parser, Subcommand, ValueEnum};
+use mass_move_lib::error::MassMoveError;
+use mass_move_lib::execute::{execute_with_progress, ExecuteOptions, Progress};
+use mass_move_lib::journal::undo;
+use mass_move_lib::rename_plan::{
+    plan_with_options, Operation, PlanOptions, RenamePlan, SymlinkStyle,
+};
+use mass_move_lib::search_by_pattern::SearchOptions;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser, Debug)]
 #[command(
     version,
-    about = "This utility allows you to transfer all files from one place to another using templates."
+    about = "This utility allows you to transfer all files from one place to another using templates.",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// It consists of a path, a name, and a '*' symbol inside the name denoting a
-    /// substring of any length (including an empty one).
+    /// substring of any length (including an empty one). A '?' symbol denotes exactly
+    /// one character, '[a-z]' or '[!0-9]' denotes one character of a class and
+    /// '{jpg,png}' denotes one of the alternatives. A name in braces like '{date}'
+    /// denotes a substring of any length that has that name.
+    /// Every '*' takes the shortest substring that lets the rest of the name match,
+    /// a greedy '*+' takes the longest one.
+    /// Directory names may contain wildcards too, and a '**' directory denotes any number
+    /// of nested directories. A backslash makes a special character ordinary, like '\*'.
     /// Example: 'path/to/some_*_filename.*'
-    #[arg(verbatim_doc_comment)]
-    pub source_pattern: String,
+    #[arg(required = true, verbatim_doc_comment)]
+    pub source_pattern: Option<String>,
 
     /// It is formed from ordinary characters, and also uses special markers
     /// like '#1', '#2', and so on. These markers indicate which fragments,
     /// indicated by asterisks in the original template, should be inserted into
-    /// the new file name.
+    /// the new file name. Markers may also be used in directory names.
+    /// Named substrings, like '{date}' in the source pattern or named groups of
+    /// a regular expression (check '--regex'), are also inserted by markers like '#{date}'.
+    /// '\#' and '\\' stand for ordinary '#' and '\' symbols.
+    /// A marker may change the case of its fragment: '#1:upper', '#1:lower',
+    /// '#1:title', '#1:snake', '#1:kebab' and '#1:camel'. Other words after ':' are
+    /// ordinary text.
     /// Example: 'path2/to/changed_#1_filename.#2'
-    #[arg(verbatim_doc_comment)]
-    pub target_pattern: String,
+    #[arg(required = true, verbatim_doc_comment)]
+    pub target_pattern: Option<String>,
 
     ///If among the names of the resulting files there are names of already existing files,
     /// the program will not throw an error and will overwrite existing files.
     #[arg(short, long, verbatim_doc_comment)]
     pub force: bool,
+
+    /// Prints every planned move and every existing file that would be replaced
+    /// without touching the filesystem.
+    #[arg(short = 'n', long, verbatim_doc_comment)]
+    pub dry_run: bool,
+
+    /// If any move fails, every already performed move is reverted,
+    /// so either all files are moved or none of them.
+    #[arg(short, long, verbatim_doc_comment)]
+    pub transactional: bool,
+
+    /// Copies matched files instead of moving them. Copies keep the permissions
+    /// and the timestamps of the originals.
+    #[arg(short, long, group = "operation", verbatim_doc_comment)]
+    pub copy: bool,
+
+    /// Creates hard links to matched files instead of moving them.
+    #[arg(short = 'l', long, group = "operation", verbatim_doc_comment)]
+    pub link: bool,
+
+    /// Creates symbolic links to matched files instead of moving them.
+    /// Links contain relative paths by default or absolute paths with '--symlink=absolute'.
+    #[arg(
+        short,
+        long,
+        group = "operation",
+        value_name = "STYLE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "relative",
+        verbatim_doc_comment
+    )]
+    pub symlink: Option<SymlinkArg>,
+
+    /// Appends the content of matched files to the ends of target files instead of
+    /// moving them. Several files may be appended to the same target file in sorted order.
+    #[arg(short, long, group = "operation", verbatim_doc_comment)]
+    pub append: bool,
+
+    /// Appends every move to this file as a line of JSON before performing it,
+    /// so that the moving can be reverted later with 'undo'.
+    #[arg(short, long, verbatim_doc_comment)]
+    pub journal: Option<PathBuf>,
+
+    /// Creates missing directories of target paths before moving files there.
+    #[arg(short = 'p', long, verbatim_doc_comment)]
+    pub create_dirs: bool,
+
+    /// Permission mode of created directories as an octal number, for example '750'.
+    #[arg(
+        long,
+        value_name = "MODE",
+        requires = "create_dirs",
+        value_parser = parse_mode,
+        verbatim_doc_comment
+    )]
+    pub dir_mode: Option<u32>,
+
+    /// How many levels of directories a '**' directory in the source pattern
+    /// matches at most.
+    #[arg(long, value_name = "DEPTH", verbatim_doc_comment)]
+    pub max_depth: Option<usize>,
+
+    /// Makes '**' directories in the source pattern descend into symbolic links
+    /// to directories.
+    #[arg(long, verbatim_doc_comment)]
+    pub follow_symlinks: bool,
+
+    /// Treats the file name part of the source pattern as a regular expression
+    /// that has to match whole file names. Its capture groups, numbered and named,
+    /// are the substrings for the target pattern.
+    /// Example: -r 'logs/(?<day>\d{8})_(\w+)\.log' 'days/#{day}/#2.log'
+    #[arg(short, long, verbatim_doc_comment)]
+    pub regex: bool,
+
+    /// Matches names of files and directories regardless of the case of letters,
+    /// so '*.jpg' also selects '.JPG' and '.Jpg' files. Substrings keep their
+    /// original case.
+    #[arg(short, long, verbatim_doc_comment)]
+    pub ignore_case: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum SymlinkArg {
+    Relative,
+    Absolute,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Moves files back according to a journal written with '--journal',
+    /// starting from the last move. Stops at the first file that was changed
+    /// or removed since it was moved.
+    #[command(verbatim_doc_comment)]
+    Undo {
+        /// Path to the journal file.
+        journal: PathBuf,
+    },
+}
+
+/// Parses an octal permission mode.
+fn parse_mode(mode: &str) -> Result<u32, String> {
+    u32::from_str_radix(mode, 8)
+        .ok()
+        .filter(|&mode| mode <= 0o7777)
+        .ok_or_else(|| format!("'{}' is not an octal permission mode", mode))
+}
+
+/// Prints the directories that are created before the files are put there.
+fn print_directories(args: &Args, rename_plan: &RenamePlan) {
+    if args.create_dirs {
+        for directory in &rename_plan.directories {
+            println!("{} (new directory)", directory);
+        }
+    }
+}
+
+/// Returns the source and the target patterns. Clap makes them required unless
+/// a subcommand is given.
+fn patterns(args: &Args) -> (&str, &str) {
+    (
+        args.source_pattern.as_deref().unwrap_or_default(),
+        args.target_pattern.as_deref().unwrap_or_default(),
+    )
+}
+
+/// Plans the operation chosen by flags.
+fn make_plan(args: &Args) -> Result<RenamePlan, MassMoveError> {
+    let (source_pattern, target_pattern) = patterns(args);
+    let options = PlanOptions {
+        search: SearchOptions {
+            max_depth: args.max_depth,
+            follow_symlinks: args.follow_symlinks,
+        },
+        regex: args.regex,
+        ignore_case: args.ignore_case,
+    };
+    let mut rename_plan = plan_with_options(source_pattern, target_pattern, &options)?;
+    if args.copy {
+        rename_plan.operation = Operation::Copy;
+    } else if args.link {
+        rename_plan.operation = Operation::HardLink;
+    } else if args.append {
+        rename_plan.operation = Operation::Append;
+    } else if let Some(style) = args.symlink {
+        rename_plan.operation = Operation::Symlink(match style {
+            SymlinkArg::Relative => SymlinkStyle::Relative,
+            SymlinkArg::Absolute => SymlinkStyle::Absolute,
+        });
+    }
+    Ok(rename_plan)
+}
+
+/// Plans and executes the moving and prints every created directory and every performed move
+/// as soon as it stays, so a failed run still shows what was done. Moves between
+/// filesystems, which were done by copying, are marked.
+fn run(args: &Args) -> Result<(), MassMoveError> {
+    let rename_plan = make_plan(args)?;
+    let options = ExecuteOptions {
+        force: args.force,
+        transactional: args.transactional,
+        journal: args.journal.clone(),
+        create_directories: args.create_dirs,
+        directory_mode: args.dir_mode,
+    };
+    execute_with_progress(&rename_plan, &options, |progress| match progress {
+        Progress::CreatedDirectory(directory) => println!("{} (new directory)", directory),
+        Progress::Performed {
+            step,
+            is_copied: true,
+        } => println!(
+            "{} -> {} (copied across filesystems)",
+            step.source, step.destination
+        ),
+        Progress::Performed { step, .. } => println!("{} -> {}", step.source, step.destination),
+    })?;
+    Ok(())
+}
+
+/// Prints the plan of moving, its conflicts and the missing directories that would not be
+/// created. Returns false if the real run would fail.
+fn dry_run(args: &Args) -> bool {
+    let rename_plan = match make_plan(args) {
+        Ok(rename_plan) => rename_plan,
+        Err(error) => {
+            eprintln!("mmv: {}", error);
+            return false;
+        }
+    };
+    print_directories(args, &rename_plan);
+    for entry in &rename_plan.entries {
+        println!("{} -> {}", entry.source, entry.destination);
+    }
+    let mut would_succeed = true;
+    for conflict in rename_plan.conflicts() {
+        if args.force && conflict.is_forceable() {
+            continue;
+        }
+        would_succeed = false;
+        eprintln!("mmv: {}", MassMoveError::from(conflict));
+    }
+    if !args.create_dirs {
+        for directory in &rename_plan.directories {
+            would_succeed = false;
+            eprintln!(
+                "mmv: Directory {} doesn't exist, use '--create-dirs' to create it",
+                directory
+            );
+        }
+    }
+    would_succeed
+}
+
+/// Undoes the moves recorded in the journal and prints every undone move.
+fn run_undo(journal: &Path) -> Result<(), MassMoveError> {
+    for entry in undo(journal)? {
+        println!("{} -> {}", entry.destination, entry.source);
+    }
+    Ok(())
 }
 
 fn main() {
     let args = Args::parse();
 
-    if mass_move(&args.source_pattern, &args.target_pattern, args.force).is_err() {
+    if let Some(Command::Undo { journal }) = &args.command {
+        if let Err(error) = run_undo(journal) {
+            eprintln!("mmv: {}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if args.dry_run {
+        if !dry_run(&args) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Err(error) = run(&args) {
+        eprintln!("mmv: {}", error);
         std::process::exit(1);
     }
 }
@@ -1,9 +1,17 @@
-use crate::string_pattern::StringPattern;
-use regex::{Captures, Regex};
+use crate::case_modifier::CaseModifier;
+use crate::error::MassMoveError;
+use crate::string_pattern::{StringPattern, ESCAPABLE_CHARACTERS};
+use regex::Regex;
 /// Returns a new string with given strings to put in relevant places in given pattern.
 /// These places look like /subsistution_pattern_prefix + num/ where num is index of string from
 /// strings_to_put. Indexing of strings to put starts with 1. If num is out of bound,
-/// function throws an error
+/// function returns [MassMoveError::PlaceholderOutOfRange].
+/// A backslash before any of [ESCAPABLE_CHARACTERS] puts that character as is, so '\#1'
+/// stays '#1' and '\\' becomes a single backslash.
+/// A place may be followed by a modifier that changes the case of the string, like
+/// '#1:upper' (check [CaseModifier] for all of them). The name of a modifier ends at the first
+/// character that is not a letter or a digit, and a name that is not a modifier, like
+/// 'lowercase' in '#1:lowercase', stays in the result as is.
 ///
 /// # Example
 /// ```
@@ -11,45 +19,110 @@ use regex::{Captures, Regex};
 ///
 /// let put: Vec<&str> = vec!["A", "B", "C"];
 /// let target_pattern: &str = "a#3b#2c#1";
-/// assert_eq!(build_by_target_pattern(&put, target_pattern, "#"), "aCbBcA");
-///
-/// // Causes an error
-/// // let empty: Vec<&str> = vec![];
-/// // let _ = build_by_target_pattern(&empty, "#1#2#3", "#");
+/// assert_eq!(build_by_target_pattern(&put, target_pattern, "#").unwrap(), "aCbBcA");
+/// assert_eq!(build_by_target_pattern(&put, r"\#1_#1", "#").unwrap(), "#1_A");
+/// assert_eq!(build_by_target_pattern(&["my photo"], "#1:snake.jpg", "#").unwrap(), "my_photo.jpg");
 ///
+/// let empty: Vec<&str> = vec![];
+/// assert!(build_by_target_pattern(&empty, "#1#2#3", "#").is_err());
 /// ```
 pub fn build_by_target_pattern(
-    strings_to_put: &Vec<&str>,
+    strings_to_put: &[&str],
     target_pattern: &str,
     subsistution_pattern_prefix: &str,
-) -> String {
-    let subsistution_pattern: String = subsistution_pattern_prefix.to_string() + r"(\d+)";
+) -> Result<String, MassMoveError> {
+    build_by_named_target_pattern(
+        strings_to_put,
+        &[],
+        target_pattern,
+        subsistution_pattern_prefix,
+    )
+}
+
+/// Does the same as [build_by_target_pattern] but strings to put may also have names, which
+/// are given in the same order. Places that look like /subsistution_pattern_prefix + {name}/
+/// get the first string with that name. If there is no such name, function returns
+/// [MassMoveError::UnknownPlaceholder].
+///
+/// # Example
+/// ```
+/// use mass_move_lib::build_by_target_pattern::build_by_named_target_pattern;
+///
+/// let put: Vec<&str> = vec!["2024", "05"];
+/// let names = vec![Some("year".to_string()), None];
+/// assert_eq!(
+///     build_by_named_target_pattern(&put, &names, "#{year}/#2_#1", "#").unwrap(),
+///     "2024/05_2024"
+/// );
+/// assert!(build_by_named_target_pattern(&put, &names, "#{month}", "#").is_err());
+/// ```
+pub fn build_by_named_target_pattern(
+    strings_to_put: &[&str],
+    names: &[Option<String>],
+    target_pattern: &str,
+    subsistution_pattern_prefix: &str,
+) -> Result<String, MassMoveError> {
+    let escapable_characters: String = ESCAPABLE_CHARACTERS.iter().collect();
+    let subsistution_pattern: String = format!(
+        r"\\([{}])|{}(?:(\d+)|\{{([^}}]*)\}})(?::([\p{{L}}\p{{N}}]+))?",
+        regex::escape(&escapable_characters),
+        regex::escape(subsistution_pattern_prefix)
+    );
     let substitution_pattern = Regex::new(subsistution_pattern.as_str()).unwrap();
 
-    let put_strings_closure = |string_index_capture: &Captures| {
-        let mut string_index = string_index_capture
-            .get(1)
-            .unwrap()
-            .as_str()
-            .parse::<usize>()
-            .unwrap();
-
-        if string_index <= strings_to_put.len() && string_index > 0 {
-            string_index -= 1;
-            strings_to_put[string_index].to_string()
-        } else {
-            panic!("Index of string to put is out of bound")
+    let mut result = String::new();
+    let mut last_match_end: usize = 0;
+    for string_index_capture in substitution_pattern.captures_iter(target_pattern) {
+        let whole_match = string_index_capture.get(0).unwrap();
+        result.push_str(&target_pattern[last_match_end..whole_match.start()]);
+        last_match_end = whole_match.end();
+        if let Some(escaped_character) = string_index_capture.get(1) {
+            result.push_str(escaped_character.as_str());
+            continue;
         }
-    };
+        let string_to_put = if let Some(name) = string_index_capture.get(3) {
+            let name_index = names
+                .iter()
+                .position(|string_name| string_name.as_deref() == Some(name.as_str()))
+                .filter(|&name_index| name_index < strings_to_put.len())
+                .ok_or_else(|| MassMoveError::UnknownPlaceholder(name.as_str().to_string()))?;
+            strings_to_put[name_index]
+        } else {
+            let string_index = string_index_capture
+                .get(2)
+                .unwrap()
+                .as_str()
+                .parse::<usize>()
+                .unwrap_or(usize::MAX);
+            if string_index > strings_to_put.len() || string_index == 0 {
+                return Err(MassMoveError::PlaceholderOutOfRange {
+                    index: string_index,
+                    available: strings_to_put.len(),
+                });
+            }
+            strings_to_put[string_index - 1]
+        };
 
-    let result = substitution_pattern
-        .replace_all(target_pattern, put_strings_closure)
-        .to_string();
-    result
+        let Some(modifier_name) = string_index_capture.get(4) else {
+            result.push_str(string_to_put);
+            continue;
+        };
+        match CaseModifier::from_name(modifier_name.as_str()) {
+            Some(modifier) => result.push_str(&modifier.apply(string_to_put)),
+            None => {
+                result.push_str(string_to_put);
+                result.push(':');
+                result.push_str(modifier_name.as_str());
+            }
+        }
+    }
+    result.push_str(&target_pattern[last_match_end..]);
+    Ok(result)
 }
 
 /// Returns a new string that is got by extracting generic parts from old string with [StringPattern]
-/// and placing them in target pattern
+/// and placing them in target pattern. Returns [None] if the string doesn't match the pattern.
+/// Named wildcards of the pattern may be placed by their names.
 ///
 /// #Examples
 /// ```
@@ -57,11 +130,20 @@ pub fn build_by_target_pattern(
 /// use mass_move_lib::build_by_target_pattern::rebuild_string_by_patterns;
 ///
 /// assert_eq!(
-///     rebuild_string_by_patterns("abcdef", &StringPattern::new("a*bcd*f"), "#1#2#1").unwrap(),
+///     rebuild_string_by_patterns("abcdef", &StringPattern::new("a*bcd*f"), "#1#2#1")
+///         .unwrap()
+///         .unwrap(),
 ///     "e"
 /// );
 /// assert_eq!(
+///     rebuild_string_by_patterns("a_b", &StringPattern::new("{x}_{y}"), "#{y}_#{x}")
+///         .unwrap()
+///         .unwrap(),
+///     "b_a"
+/// );
+/// assert_eq!(
 ///     rebuild_string_by_patterns("abcdef", &StringPattern::new("a*cdef"), "123_#1_123")
+///         .unwrap()
 ///         .unwrap(),
 ///     "123_b_123"
 /// );
@@ -70,44 +152,143 @@ pub fn rebuild_string_by_patterns(
     string_to_rebuild: &str,
     string_pattern: &StringPattern,
     target_pattern: &str,
-) -> Option<String> {
-    let generic_parts = string_pattern.get_generic_parts(string_to_rebuild);
-    generic_parts.as_ref()?;
-
-    let generic_parts = generic_parts.unwrap();
-    Some(build_by_target_pattern(&generic_parts, target_pattern, "#"))
+) -> Result<Option<String>, MassMoveError> {
+    let Some(generic_parts) = string_pattern.get_generic_parts(string_to_rebuild) else {
+        return Ok(None);
+    };
+    build_by_named_target_pattern(
+        &generic_parts,
+        &string_pattern.capture_names(),
+        target_pattern,
+        "#",
+    )
+    .map(Some)
 }
 
 #[cfg(test)]
 mod tests {
     #[cfg(test)]
     mod test_build_by_target_pattern {
-        use crate::build_by_target_pattern::build_by_target_pattern;
+        use crate::build_by_target_pattern::{
+            build_by_named_target_pattern, build_by_target_pattern,
+        };
+        use crate::error::MassMoveError;
 
         #[test]
         fn test_empty() {
             let put: Vec<&str> = vec![];
-            assert_eq!(build_by_target_pattern(&put, "", "#"), "");
+            assert_eq!(build_by_target_pattern(&put, "", "#").unwrap(), "");
         }
 
         #[test]
-        #[should_panic(expected = "Index of string to put is out of bound")]
         fn test_out_of_index() {
             let empty_vector: Vec<&str> = vec![];
-            let _ = build_by_target_pattern(&empty_vector, "#1#2#3", "#");
+            assert!(matches!(
+                build_by_target_pattern(&empty_vector, "#1#2#3", "#"),
+                Err(MassMoveError::PlaceholderOutOfRange {
+                    index: 1,
+                    available: 0
+                })
+            ));
+
+            let put: Vec<&str> = vec!["A"];
+            assert!(matches!(
+                build_by_target_pattern(&put, "#1#0", "#"),
+                Err(MassMoveError::PlaceholderOutOfRange {
+                    index: 0,
+                    available: 1
+                })
+            ));
         }
 
         #[test]
         fn test_few_cases() {
             let put: Vec<&str> = vec!["A", "B", "C"];
-            assert_eq!(build_by_target_pattern(&put, "a#1b#2c#1", "#"), "aAbBcA");
-            assert_eq!(build_by_target_pattern(&put, "a#3b#2c#1", "#"), "aCbBcA");
+            assert_eq!(
+                build_by_target_pattern(&put, "a#1b#2c#1", "#").unwrap(),
+                "aAbBcA"
+            );
+            assert_eq!(
+                build_by_target_pattern(&put, "a#3b#2c#1", "#").unwrap(),
+                "aCbBcA"
+            );
 
             let put: Vec<&str> = vec!["A", "B", "C"];
-            assert_eq!(build_by_target_pattern(&put, "#1#2#1", "#"), "ABA");
+            assert_eq!(build_by_target_pattern(&put, "#1#2#1", "#").unwrap(), "ABA");
 
             let put: Vec<&str> = vec!["A", "B", "C"];
-            assert_eq!(build_by_target_pattern(&put, "", "#"), "");
+            assert_eq!(build_by_target_pattern(&put, "", "#").unwrap(), "");
+        }
+
+        #[test]
+        fn test_escapes() {
+            let put: Vec<&str> = vec!["A"];
+            assert_eq!(
+                build_by_target_pattern(&put, r"\#1#1\\#1", "#").unwrap(),
+                r"#1A\A"
+            );
+            assert_eq!(
+                build_by_target_pattern(&put, r"\*\a\", "#").unwrap(),
+                r"*\a\"
+            );
+            assert_eq!(build_by_target_pattern(&put, r"\#5", "#").unwrap(), "#5");
+        }
+
+        #[test]
+        fn test_modifiers() {
+            let put: Vec<&str> = vec!["My Photo", "JPG"];
+            assert_eq!(
+                build_by_target_pattern(&put, "#1:kebab.#2:lower", "#").unwrap(),
+                "my-photo.jpg"
+            );
+            assert_eq!(
+                build_by_target_pattern(&put, "#1:camel_#1:upper_#1:title", "#").unwrap(),
+                "myPhoto_MY PHOTO_My Photo"
+            );
+            assert_eq!(
+                build_by_target_pattern(&put, "#2:unknown", "#").unwrap(),
+                "JPG:unknown"
+            );
+            assert_eq!(
+                build_by_target_pattern(&put, r"\#1:upper", "#").unwrap(),
+                "#1:upper"
+            );
+            assert_eq!(
+                build_by_target_pattern(&["Name_x"], "#1:lowercase.txt", "#").unwrap(),
+                "Name_x:lowercase.txt"
+            );
+            assert_eq!(
+                build_by_target_pattern(&put, "#2:lower2:upper-#2:lowerй", "#").unwrap(),
+                "JPG:lower2:upper-JPG:lowerй"
+            );
+
+            let names = vec![Some("name".to_string()), None];
+            assert_eq!(
+                build_by_named_target_pattern(&put, &names, "#{name}:snake", "#").unwrap(),
+                "my_photo"
+            );
+        }
+
+        #[test]
+        fn test_named() {
+            let put: Vec<&str> = vec!["2024", "05", "x"];
+            let names = vec![Some("year".to_string()), None, Some("year".to_string())];
+            assert_eq!(
+                build_by_named_target_pattern(&put, &names, "#{year}-#2-#3", "#").unwrap(),
+                "2024-05-x"
+            );
+            assert_eq!(
+                build_by_named_target_pattern(&put, &names, r"\#{year}", "#").unwrap(),
+                "#{year}"
+            );
+            assert!(matches!(
+                build_by_named_target_pattern(&put, &names, "#{month}", "#"),
+                Err(MassMoveError::UnknownPlaceholder(name)) if name == "month"
+            ));
+            assert!(matches!(
+                build_by_target_pattern(&put, "#{year}", "#"),
+                Err(MassMoveError::UnknownPlaceholder(_))
+            ));
         }
     }
 
@@ -119,7 +300,9 @@ mod tests {
         #[test]
         fn test_empty() {
             assert_eq!(
-                rebuild_string_by_patterns("", &StringPattern::new(""), "").unwrap(),
+                rebuild_string_by_patterns("", &StringPattern::new(""), "")
+                    .unwrap()
+                    .unwrap(),
                 ""
             );
         }
@@ -127,19 +310,31 @@ mod tests {
         #[test]
         fn test_normal() {
             assert_eq!(
-                rebuild_string_by_patterns("abcdef", &StringPattern::new("a*f"), "#1").unwrap(),
+                rebuild_string_by_patterns("abcdef", &StringPattern::new("a*f"), "#1")
+                    .unwrap()
+                    .unwrap(),
                 "bcde"
             );
             assert_eq!(
                 rebuild_string_by_patterns("abcdef", &StringPattern::new("a*bcd*f"), "#1#2#1")
+                    .unwrap()
                     .unwrap(),
                 "e"
             );
             assert_eq!(
                 rebuild_string_by_patterns("abcdef", &StringPattern::new("a*cdef"), "123_#1_123")
+                    .unwrap()
                     .unwrap(),
                 "123_b_123"
             );
         }
+
+        #[test]
+        fn test_not_matching() {
+            assert_eq!(
+                rebuild_string_by_patterns("abcdef", &StringPattern::new("b*"), "#1").unwrap(),
+                None
+            );
+        }
     }
 }
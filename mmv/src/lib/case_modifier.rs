@@ -0,0 +1,159 @@
+/// A change of letter case that is applied to a substring put into a target pattern,
+/// like '#1:snake'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseModifier {
+    /// 'IMG Beach' -> 'IMG BEACH'
+    Upper,
+    /// 'IMG Beach' -> 'img beach'
+    Lower,
+    /// 'my_BEACH photo' -> 'My_Beach Photo', separators are kept.
+    Title,
+    /// 'myBeach photo' -> 'my_beach_photo'
+    Snake,
+    /// 'myBeach photo' -> 'my-beach-photo'
+    Kebab,
+    /// 'my_beach photo' -> 'myBeachPhoto'
+    Camel,
+}
+
+impl CaseModifier {
+    /// Names of all modifiers as they are written in target patterns.
+    pub const NAMES: [&'static str; 6] = ["upper", "lower", "title", "snake", "kebab", "camel"];
+
+    /// Returns the modifier with the given name or [None] if there is no such modifier.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "upper" => Some(CaseModifier::Upper),
+            "lower" => Some(CaseModifier::Lower),
+            "title" => Some(CaseModifier::Title),
+            "snake" => Some(CaseModifier::Snake),
+            "kebab" => Some(CaseModifier::Kebab),
+            "camel" => Some(CaseModifier::Camel),
+            _ => None,
+        }
+    }
+
+    /// Returns the text with the case changed. Words of the text are separated by any
+    /// characters but letters and digits, and by a capital letter that follows a small one
+    /// or starts a capitalized word after an abbreviation, like in 'HTTPServer'.
+    ///
+    /// # Examples
+    /// ```
+    /// use mass_move_lib::case_modifier::CaseModifier;
+    ///
+    /// assert_eq!(CaseModifier::Snake.apply("HTTPServer log"), "http_server_log");
+    /// assert_eq!(CaseModifier::Camel.apply("beach-photo_2024"), "beachPhoto2024");
+    /// assert_eq!(CaseModifier::Title.apply("beach photo"), "Beach Photo");
+    /// ```
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            CaseModifier::Upper => text.to_uppercase(),
+            CaseModifier::Lower => text.to_lowercase(),
+            CaseModifier::Title => {
+                let mut result = String::new();
+                let mut is_word_start = true;
+                for letter in text.chars() {
+                    if is_word_start {
+                        result.extend(letter.to_uppercase());
+                    } else {
+                        result.extend(letter.to_lowercase());
+                    }
+                    is_word_start = !letter.is_alphanumeric();
+                }
+                result
+            }
+            CaseModifier::Snake => Self::split_words(text).join("_").to_lowercase(),
+            CaseModifier::Kebab => Self::split_words(text).join("-").to_lowercase(),
+            CaseModifier::Camel => {
+                let mut result = String::new();
+                for (index, word) in Self::split_words(text).iter().enumerate() {
+                    if index == 0 {
+                        result.push_str(&word.to_lowercase());
+                    } else {
+                        result.push_str(&CaseModifier::Title.apply(word));
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    /// Splits the text into words (check [CaseModifier::apply]).
+    fn split_words(text: &str) -> Vec<String> {
+        let letters: Vec<char> = text.chars().collect();
+        let mut words: Vec<String> = Vec::new();
+        let mut word = String::new();
+        for (index, &letter) in letters.iter().enumerate() {
+            if !letter.is_alphanumeric() {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+                continue;
+            }
+            if letter.is_uppercase() && index > 0 {
+                let previous = letters[index - 1];
+                let next = letters.get(index + 1);
+                let after_small = previous.is_lowercase() || previous.is_numeric();
+                let ends_abbreviation =
+                    previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase());
+                if (after_small || ends_abbreviation) && !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            word.push(letter);
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+        words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(test)]
+    mod test_apply {
+        use crate::case_modifier::CaseModifier;
+
+        #[test]
+        fn test_names() {
+            for name in CaseModifier::NAMES {
+                assert!(CaseModifier::from_name(name).is_some(), "{}", name);
+            }
+            assert_eq!(CaseModifier::from_name("Upper"), None);
+        }
+
+        #[test]
+        fn test_upper_and_lower() {
+            assert_eq!(CaseModifier::Upper.apply("Straße_1"), "STRASSE_1");
+            assert_eq!(CaseModifier::Lower.apply("IMG_Beach"), "img_beach");
+        }
+
+        #[test]
+        fn test_title() {
+            assert_eq!(
+                CaseModifier::Title.apply("my_BEACH photo-2"),
+                "My_Beach Photo-2"
+            );
+            assert_eq!(CaseModifier::Title.apply(""), "");
+        }
+
+        #[test]
+        fn test_word_conversions() {
+            let text = "  myBeach PHOTO_2024-HTTPServer ";
+            assert_eq!(
+                CaseModifier::Snake.apply(text),
+                "my_beach_photo_2024_http_server"
+            );
+            assert_eq!(
+                CaseModifier::Kebab.apply(text),
+                "my-beach-photo-2024-http-server"
+            );
+            assert_eq!(
+                CaseModifier::Camel.apply(text),
+                "myBeachPhoto2024HttpServer"
+            );
+            assert_eq!(CaseModifier::Snake.apply("__"), "");
+        }
+    }
+}
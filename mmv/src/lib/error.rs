@@ -0,0 +1,152 @@
+use crate::rename_plan::Operation;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong while planning or executing a mass move.
+#[derive(Debug)]
+pub enum MassMoveError {
+    /// The source pattern can't be split into a directory and a file name pattern.
+    InvalidSourcePattern(String),
+    /// The target pattern can't be split into a directory and a file name pattern.
+    InvalidTargetPattern(String),
+    /// No file matches the source pattern.
+    NoMatches(String),
+    /// The destination file already exists and replacing it was not allowed.
+    DestinationExists(String),
+    /// The destination is the source of another file that is copied, linked or appended
+    /// (check [crate::rename_plan::Conflict::DestinationIsSource]).
+    DestinationIsSource(String),
+    /// Several sources would be moved to the same destination.
+    DuplicateDestination {
+        destination: String,
+        sources: Vec<String>,
+    },
+    /// The target pattern refers to '#index' but only `available` substrings can be put.
+    PlaceholderOutOfRange { index: usize, available: usize },
+    /// The target pattern refers to '#{name}' but the source pattern has no substring
+    /// with that name.
+    UnknownPlaceholder(String),
+    /// The filesystem refused to move (or otherwise apply the operation to) `source`
+    /// to `dest`.
+    Io {
+        operation: Operation,
+        source: String,
+        dest: String,
+        err: io::Error,
+    },
+    /// A missing directory of a destination couldn't be created.
+    CreateDirectory { path: String, err: io::Error },
+    /// A file created or changed by the execution couldn't be removed or restored while
+    /// reverting the change.
+    Revert { path: String, err: io::Error },
+    /// The journal file can't be opened, read or written.
+    Journal { path: String, err: io::Error },
+    /// The line of the journal file is not a valid journal entry.
+    InvalidJournal { path: String, line: usize },
+    /// The file recorded in the journal was changed or removed since it was moved.
+    JournalMismatch(String),
+    /// A transactional execution failed and the performed moves were reverted.
+    /// If `rollback_errors` is not empty some of them couldn't be reverted.
+    RolledBack {
+        failure: Box<MassMoveError>,
+        rollback_errors: Vec<MassMoveError>,
+    },
+}
+
+impl fmt::Display for MassMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MassMoveError::InvalidSourcePattern(pattern) => {
+                write!(f, "Incorrect source pattern '{}'", pattern)
+            }
+            MassMoveError::InvalidTargetPattern(pattern) => {
+                write!(f, "Incorrect target pattern '{}'", pattern)
+            }
+            MassMoveError::NoMatches(pattern) => {
+                write!(f, "Files for pattern '{}' not found", pattern)
+            }
+            MassMoveError::DestinationExists(path) => {
+                write!(f, "Not able to replace existing file: {}", path)
+            }
+            MassMoveError::DestinationIsSource(path) => write!(
+                f,
+                "Not able to replace {}, it is a source of another file itself",
+                path
+            ),
+            MassMoveError::DuplicateDestination {
+                destination,
+                sources,
+            } => write!(
+                f,
+                "Several files would be moved to {}: {}",
+                destination,
+                sources.join(", ")
+            ),
+            MassMoveError::PlaceholderOutOfRange { index, available } => write!(
+                f,
+                "Index of string to put is out of bound: #{} (only {} available)",
+                index, available
+            ),
+            MassMoveError::UnknownPlaceholder(name) => write!(
+                f,
+                "There is no substring named '{}' in the source pattern",
+                name
+            ),
+            MassMoveError::Io {
+                operation,
+                source,
+                dest,
+                err,
+            } => write!(
+                f,
+                "Couldn't {} {} to {}: {}",
+                operation.verb(),
+                source,
+                dest,
+                err
+            ),
+            MassMoveError::CreateDirectory { path, err } => {
+                write!(f, "Couldn't create directory {}: {}", path, err)
+            }
+            MassMoveError::Revert { path, err } => {
+                write!(f, "Couldn't revert changes of {}: {}", path, err)
+            }
+            MassMoveError::Journal { path, err } => {
+                write!(f, "Couldn't access journal {}: {}", path, err)
+            }
+            MassMoveError::InvalidJournal { path, line } => {
+                write!(f, "Invalid entry in journal {} at line {}", path, line)
+            }
+            MassMoveError::JournalMismatch(path) => {
+                write!(f, "File {} doesn't match the journal", path)
+            }
+            MassMoveError::RolledBack {
+                failure,
+                rollback_errors,
+            } => {
+                if rollback_errors.is_empty() {
+                    return write!(f, "{}; all performed moves were reverted", failure);
+                }
+                write!(f, "{}; rollback failed:", failure)?;
+                for rollback_error in rollback_errors {
+                    write!(f, "\n  {}", rollback_error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for MassMoveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MassMoveError::Io { err, .. } => Some(err),
+            MassMoveError::CreateDirectory { err, .. } => Some(err),
+            MassMoveError::Revert { err, .. } => Some(err),
+            MassMoveError::Journal { err, .. } => Some(err),
+            MassMoveError::RolledBack { failure, .. } => Some(failure.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,492 @@
+use crate::error::MassMoveError;
+use crate::file_operations::{
+    append_file, copy_file, create_directory, hard_link, is_occupied, move_file, symlink,
+    truncate_file,
+};
+use crate::journal::Journal;
+use crate::rename_plan::{Operation, RenamePlan};
+use crate::schedule::{make_temporary_name, normalize_path, schedule_moves, Step};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+/// Options that change how [execute] applies a [RenamePlan].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecuteOptions {
+    /// Replace existing destination files instead of refusing to execute the plan.
+    pub force: bool,
+    /// Execute the plan all-or-nothing: if any move fails, every performed move is reverted.
+    pub transactional: bool,
+    /// File where every move is recorded before it is performed, so that the whole execution
+    /// can be undone later (check [crate::journal::undo]).
+    pub journal: Option<PathBuf>,
+    /// Create the missing directories listed in [RenamePlan::directories] before applying the
+    /// operation. Otherwise files can't be put into missing directories.
+    pub create_directories: bool,
+    /// Permission mode of created directories (unix only). The default mode limited by
+    /// the umask is used if it is [None].
+    pub directory_mode: Option<u32>,
+}
+
+/// Details of a successful [execute].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecuteReport {
+    /// Moves between different filesystems, which were performed by copying files and removing
+    /// the originals (check [crate::file_operations::move_by_copying]).
+    pub copied_moves: Vec<Step>,
+}
+
+/// Applies the operation of the plan to every file. Files are moved in the order computed by
+/// [schedule_moves], so chains and cycles of renames within the plan are applied safely.
+/// Files are copied, linked and appended in the order of plan entries. Missing directories
+/// are created before that if the create directories option is set.
+/// If the plan has conflicts nothing is done. Existing destination files are replaced only
+/// if force option is set, destinations shared by several sources are never allowed
+/// (except for appending, check [RenamePlan::conflicts]).
+///
+/// The first failed operation stops the execution and is returned as [MassMoveError::Io].
+/// In transactional mode everything done before it is reverted in reverse order,
+/// including files replaced because of the force option and created directories,
+/// and [MassMoveError::RolledBack]
+/// is returned instead.
+///
+/// If a journal is set, every operation on a file, including moves to temporary names and moves of
+/// a rollback, is recorded there before it is performed.
+///
+/// Files that can't be renamed because the destination is on another filesystem are copied
+/// and then removed. Such moves are listed in the returned [ExecuteReport].
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs::File;
+/// # use std::path::Path;
+/// use mass_move_lib::execute::{execute, ExecuteOptions};
+/// use mass_move_lib::rename_plan::plan;
+///
+/// let directory = tempfile::TempDir::new().unwrap();
+/// let path = directory.path().to_str().unwrap();
+/// File::create(format!("{}/a.txt", path)).unwrap();
+/// File::create(format!("{}/b.txt", path)).unwrap();
+///
+/// let mut rename_plan = plan(&format!("{}/*.txt", path), &format!("{}/#1.bin", path)).unwrap();
+/// rename_plan.entries.retain(|entry| entry.captures[0] != "b");
+/// execute(&rename_plan, &ExecuteOptions::default()).unwrap();
+///
+/// assert!(Path::new(&format!("{}/a.bin", path)).exists());
+/// assert!(Path::new(&format!("{}/b.txt", path)).exists());
+/// ```
+pub fn execute(
+    plan: &RenamePlan,
+    options: &ExecuteOptions,
+) -> Result<ExecuteReport, MassMoveError> {
+    execute_with_progress(plan, options, |_| {})
+}
+
+/// A change made by [execute_with_progress] that is reported as soon as it stays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress<'a> {
+    /// A missing directory was created.
+    CreatedDirectory(&'a str),
+    /// The operation was applied to a file. Moves to temporary names that break cycles are
+    /// steps too. `is_copied` is true for a move between filesystems that was done by copying.
+    Performed { step: &'a Step, is_copied: bool },
+}
+
+/// Does the same as [execute] and calls `on_progress` for every change that was made, so
+/// that a caller can tell what happened even if the execution fails halfway. Changes are
+/// reported right after they are made, and in transactional mode only when the whole
+/// execution succeeded, since otherwise they are reverted.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs::File;
+/// use mass_move_lib::execute::{execute_with_progress, ExecuteOptions, Progress};
+/// use mass_move_lib::rename_plan::plan;
+///
+/// let directory = tempfile::TempDir::new().unwrap();
+/// let path = directory.path().to_str().unwrap();
+/// File::create(format!("{}/a.txt", path)).unwrap();
+///
+/// let rename_plan = plan(&format!("{}/*.txt", path), &format!("{}/#1.bin", path)).unwrap();
+/// let mut destinations: Vec<String> = Vec::new();
+/// execute_with_progress(&rename_plan, &ExecuteOptions::default(), |progress| {
+///     if let Progress::Performed { step, .. } = progress {
+///         destinations.push(step.destination.clone());
+///     }
+/// })
+/// .unwrap();
+///
+/// assert_eq!(destinations, vec![format!("{}/a.bin", path)]);
+/// ```
+pub fn execute_with_progress<F: FnMut(Progress)>(
+    plan: &RenamePlan,
+    options: &ExecuteOptions,
+    mut on_progress: F,
+) -> Result<ExecuteReport, MassMoveError> {
+    let blocking_conflict = plan
+        .conflicts()
+        .into_iter()
+        .find(|conflict| !options.force || !conflict.is_forceable());
+    if let Some(conflict) = blocking_conflict {
+        return Err(conflict.into());
+    }
+
+    let journal = match &options.journal {
+        Some(path) => Some(Journal::open(path)?),
+        None => None,
+    };
+    let mut operator = Operator {
+        journal,
+        copied_moves: Vec::new(),
+    };
+
+    let directories: &[String] = if options.create_directories {
+        &plan.directories
+    } else {
+        &[]
+    };
+    let steps: Vec<Step> = match plan.operation {
+        Operation::Move => schedule_moves(plan),
+        _ => plan
+            .entries
+            .iter()
+            .filter(|entry| !entry.is_unchanged())
+            .map(|entry| Step {
+                source: entry.source.clone(),
+                destination: entry.destination.clone(),
+            })
+            .collect(),
+    };
+    if !options.transactional {
+        for directory in directories {
+            make_directory(directory, options.directory_mode)?;
+            on_progress(Progress::CreatedDirectory(directory));
+        }
+        for step in &steps {
+            if options.force {
+                remove_replaced(plan.operation, &step.source, &step.destination)?;
+            }
+            let is_copied = operator.apply(plan.operation, &step.source, &step.destination)?;
+            on_progress(Progress::Performed { step, is_copied });
+        }
+        return Ok(operator.report());
+    }
+
+    let mut transaction = Transaction::new(operator, &steps);
+    let result = transaction
+        .create_directories(directories, options.directory_mode)
+        .and_then(|()| transaction.run(plan.operation, &steps));
+    match result {
+        Ok(()) => Ok(transaction.commit(on_progress)),
+        Err(failure) => Err(transaction.rollback(failure)),
+    }
+}
+
+/// Creates a directory, turning a failure into [MassMoveError::CreateDirectory].
+fn make_directory(path: &str, mode: Option<u32>) -> Result<(), MassMoveError> {
+    create_directory(path, mode).map_err(|err| MassMoveError::CreateDirectory {
+        path: path.to_string(),
+        err,
+    })
+}
+
+/// Removes an existing destination before a forced copy or link puts a new file there.
+/// Copying into it would write through a link and could truncate the source itself, and
+/// links can't be created over existing files. Moves replace files by themselves.
+fn remove_replaced(
+    operation: Operation,
+    source: &str,
+    destination: &str,
+) -> Result<(), MassMoveError> {
+    let is_replaced = matches!(
+        operation,
+        Operation::Copy | Operation::HardLink | Operation::Symlink(_)
+    );
+    if !is_replaced || !is_occupied(destination) {
+        return Ok(());
+    }
+    fs::remove_file(destination).map_err(|err| MassMoveError::Io {
+        operation,
+        source: source.to_string(),
+        dest: destination.to_string(),
+        err,
+    })
+}
+
+/// Applies the operation to single files and records them in the journal if there is one.
+struct Operator {
+    journal: Option<Journal>,
+    copied_moves: Vec<Step>,
+}
+
+impl Operator {
+    /// Moves, copies, links or appends a file, turning a failure into [MassMoveError::Io].
+    /// Returns true if the file was moved to another filesystem by copying.
+    fn apply(
+        &mut self,
+        operation: Operation,
+        source: &str,
+        destination: &str,
+    ) -> Result<bool, MassMoveError> {
+        if let Some(journal) = &mut self.journal {
+            journal.record(operation, source, destination)?;
+        }
+        let result = match operation {
+            Operation::Move => move_file(source, destination).inspect(|&is_copied| {
+                if is_copied {
+                    self.copied_moves.push(Step {
+                        source: source.to_string(),
+                        destination: destination.to_string(),
+                    });
+                }
+            }),
+            Operation::Copy => copy_file(source, destination).map(|()| false),
+            Operation::HardLink => hard_link(source, destination).map(|()| false),
+            Operation::Symlink(style) => symlink(source, destination, style).map(|()| false),
+            Operation::Append => append_file(source, destination).map(|()| false),
+        };
+        result.map_err(|err| MassMoveError::Io {
+            operation,
+            source: source.to_string(),
+            dest: destination.to_string(),
+            err,
+        })
+    }
+
+    fn report(self) -> ExecuteReport {
+        ExecuteReport {
+            copied_moves: self.copied_moves,
+        }
+    }
+}
+
+/// A change of the filesystem made by a transactional [execute] that can be reverted.
+enum Action {
+    /// The file was moved from source to destination.
+    Moved(Step),
+    /// A new file was created at the path.
+    Created(String),
+    /// A new directory was created at the path.
+    CreatedDirectory(String),
+    /// A file was appended to the file at the path, which had the given length before or
+    /// didn't exist.
+    Appended {
+        path: String,
+        previous_length: Option<u64>,
+    },
+}
+
+/// Changes made by a transactional [execute] and files that were put aside instead of
+/// being replaced, so that everything can be reverted.
+struct Transaction {
+    operator: Operator,
+    performed: Vec<Action>,
+    /// Steps of the plan that were performed and whether they were moved by copying.
+    performed_steps: Vec<(Step, bool)>,
+    backups: Vec<String>,
+    used_paths: HashSet<PathBuf>,
+    backups_count: usize,
+}
+
+impl Transaction {
+    fn new(operator: Operator, steps: &[Step]) -> Self {
+        Self {
+            operator,
+            performed: Vec::new(),
+            performed_steps: Vec::new(),
+            backups: Vec::new(),
+            used_paths: steps
+                .iter()
+                .flat_map(|step| [&step.source, &step.destination])
+                .map(|path| normalize_path(path))
+                .collect(),
+            backups_count: 0,
+        }
+    }
+
+    fn create_directories(
+        &mut self,
+        directories: &[String],
+        mode: Option<u32>,
+    ) -> Result<(), MassMoveError> {
+        for directory in directories {
+            make_directory(directory, mode)?;
+            self.performed
+                .push(Action::CreatedDirectory(directory.clone()));
+        }
+        Ok(())
+    }
+
+    /// Performs the steps one by one. An existing destination is moved to a backup name first
+    /// unless files are appended to it.
+    fn run(&mut self, operation: Operation, steps: &[Step]) -> Result<(), MassMoveError> {
+        for step in steps {
+            if operation != Operation::Append && is_occupied(&step.destination) {
+                let backup = make_temporary_name(
+                    &step.destination,
+                    "mmv-backup",
+                    &self.used_paths,
+                    &mut self.backups_count,
+                );
+                self.perform(Operation::Move, &step.destination, &backup)?;
+                self.backups.push(backup);
+            }
+            let is_copied = self.perform(operation, &step.source, &step.destination)?;
+            self.performed_steps.push((step.clone(), is_copied));
+        }
+        Ok(())
+    }
+
+    fn perform(
+        &mut self,
+        operation: Operation,
+        source: &str,
+        destination: &str,
+    ) -> Result<bool, MassMoveError> {
+        let previous_length = fs::metadata(destination)
+            .ok()
+            .map(|metadata| metadata.len());
+        let is_copied = self.operator.apply(operation, source, destination)?;
+        self.performed.push(match operation {
+            Operation::Move => Action::Moved(Step {
+                source: source.to_string(),
+                destination: destination.to_string(),
+            }),
+            Operation::Append => Action::Appended {
+                path: destination.to_string(),
+                previous_length,
+            },
+            _ => Action::Created(destination.to_string()),
+        });
+        Ok(is_copied)
+    }
+
+    /// Removes the replaced files that were kept for a possible rollback and reports
+    /// created directories and performed steps. Removals are recorded in the journal first;
+    /// a file whose removal can't be recorded is kept.
+    fn commit<F: FnMut(Progress)>(mut self, mut on_progress: F) -> ExecuteReport {
+        for action in &self.performed {
+            if let Action::CreatedDirectory(directory) = action {
+                on_progress(Progress::CreatedDirectory(directory));
+            }
+        }
+        for (step, is_copied) in &self.performed_steps {
+            on_progress(Progress::Performed {
+                step,
+                is_copied: *is_copied,
+            });
+        }
+        for backup in &self.backups {
+            if let Some(journal) = &mut self.operator.journal {
+                if journal.record_removal(backup).is_err() {
+                    continue;
+                }
+            }
+            let _ = fs::remove_file(backup);
+        }
+        self.operator.report()
+    }
+
+    /// Reverts every performed change in reverse order and returns the error describing both
+    /// the failure and the outcome of the rollback.
+    fn rollback(mut self, failure: MassMoveError) -> MassMoveError {
+        let rollback_errors: Vec<MassMoveError> = self
+            .performed
+            .iter()
+            .rev()
+            .filter_map(|action| match action {
+                Action::Moved(step) => self
+                    .operator
+                    .apply(Operation::Move, &step.destination, &step.source)
+                    .err(),
+                Action::Created(path)
+                | Action::Appended {
+                    path,
+                    previous_length: None,
+                } => fs::remove_file(path)
+                    .map_err(|err| MassMoveError::Revert {
+                        path: path.clone(),
+                        err,
+                    })
+                    .err(),
+                Action::CreatedDirectory(path) => fs::remove_dir(path)
+                    .map_err(|err| MassMoveError::Revert {
+                        path: path.clone(),
+                        err,
+                    })
+                    .err(),
+                Action::Appended {
+                    path,
+                    previous_length: Some(length),
+                } => truncate_file(path, *length)
+                    .map_err(|err| MassMoveError::Revert {
+                        path: path.clone(),
+                        err,
+                    })
+                    .err(),
+            })
+            .collect();
+        MassMoveError::RolledBack {
+            failure: Box::new(failure),
+            rollback_errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(test)]
+    mod test_copied_moves {
+        use crate::execute::{execute_with_progress, ExecuteOptions, Progress};
+        use crate::file_operations::IS_CROSSING_DEVICES;
+        use crate::rename_plan::plan;
+        use crate::schedule::Step;
+        use std::fs;
+        use tempfile::TempDir;
+
+        #[test]
+        fn test_moves_between_filesystems_are_reported() {
+            for transactional in [false, true] {
+                let temporary_directory = TempDir::new().unwrap();
+                let directory_path = temporary_directory.path().to_str().unwrap();
+                fs::write(format!("{}/a_1", directory_path), "a1").unwrap();
+                let rename_plan = plan(
+                    &(directory_path.to_string() + "/a_*"),
+                    &(directory_path.to_string() + "/b_#1"),
+                )
+                .unwrap();
+                let options = ExecuteOptions {
+                    transactional,
+                    ..ExecuteOptions::default()
+                };
+                let mut copied_steps: Vec<Step> = Vec::new();
+
+                IS_CROSSING_DEVICES.set(true);
+                let result = execute_with_progress(&rename_plan, &options, |progress| {
+                    if let Progress::Performed {
+                        step,
+                        is_copied: true,
+                    } = progress
+                    {
+                        copied_steps.push(step.clone());
+                    }
+                });
+                IS_CROSSING_DEVICES.set(false);
+
+                let expected_steps = vec![Step {
+                    source: format!("{}/a_1", directory_path),
+                    destination: format!("{}/b_1", directory_path),
+                }];
+                assert_eq!(result.unwrap().copied_moves, expected_steps);
+                assert_eq!(copied_steps, expected_steps);
+                assert!(fs::metadata(format!("{}/a_1", directory_path)).is_err());
+                assert_eq!(
+                    fs::read_to_string(format!("{}/b_1", directory_path)).unwrap(),
+                    "a1"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,563 @@
+use crate::rename_plan::SymlinkStyle;
+use std::fs::{self, File, FileTimes};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Component, Path, PathBuf};
+
+/// Returns true if anything, including a dangling symbolic link, exists at the path.
+pub fn is_occupied<P: AsRef<Path>>(path: P) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+/// Returns true if both paths lead to the same existing file, through symbolic or hard links.
+/// Where files have no known identity (not on unix), only symbolic links are recognised,
+/// by comparing the canonical paths.
+pub fn is_same_file<P: AsRef<Path>, Q: AsRef<Path>>(path: P, other_path: Q) -> bool {
+    let (Ok(metadata), Ok(other_metadata)) = (fs::metadata(&path), fs::metadata(&other_path))
+    else {
+        return false;
+    };
+    match (file_id(&metadata), file_id(&other_metadata)) {
+        (Some(id), Some(other_id)) => id == other_id,
+        _ => match (fs::canonicalize(path), fs::canonicalize(other_path)) {
+            (Ok(canonical_path), Ok(other_canonical_path)) => {
+                canonical_path == other_canonical_path
+            }
+            _ => false,
+        },
+    }
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Copies the content of a file to a new place and gives the copy the same permissions and
+/// the same access and modification times as the original has.
+/// Fails without touching anything if the destination is the source itself or a link to it,
+/// since copying would truncate the source.
+///
+/// # Examples
+/// ```
+/// # use std::fs;
+/// use mass_move_lib::file_operations::copy_file;
+///
+/// let directory = tempfile::TempDir::new().unwrap();
+/// let source = directory.path().join("a.txt");
+/// let destination = directory.path().join("b.txt");
+/// fs::write(&source, "hihihihi").unwrap();
+///
+/// copy_file(&source, &destination).unwrap();
+///
+/// assert_eq!(fs::read_to_string(&destination).unwrap(), "hihihihi");
+/// assert_eq!(
+///     fs::metadata(&source).unwrap().modified().unwrap(),
+///     fs::metadata(&destination).unwrap().modified().unwrap()
+/// );
+/// ```
+pub fn copy_file<P: AsRef<Path>, Q: AsRef<Path>>(source: P, destination: Q) -> io::Result<()> {
+    let metadata = fs::metadata(&source)?;
+    if is_same_file(&source, &destination) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "destination is the same file as the source",
+        ));
+    }
+    fs::copy(&source, &destination)?;
+
+    let times = FileTimes::new()
+        .set_accessed(metadata.accessed()?)
+        .set_modified(metadata.modified()?);
+    File::options()
+        .write(true)
+        .open(&destination)?
+        .set_times(times)
+}
+
+/// Creates a directory whose parent exists. On unix the directory gets the given permission
+/// mode if there is one, regardless of the umask; elsewhere the mode is ignored.
+pub fn create_directory<P: AsRef<Path>>(path: P, mode: Option<u32>) -> io::Result<()> {
+    fs::create_dir(&path)?;
+    #[cfg(unix)]
+    if let Some(mode) = mode {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+    Ok(())
+}
+
+/// Moves a file. If the paths are on different filesystems, which [fs::rename] can't do,
+/// the file is moved by [move_by_copying] instead. Returns true in that case.
+pub fn move_file<P: AsRef<Path>, Q: AsRef<Path>>(source: P, destination: Q) -> io::Result<bool> {
+    match rename(source.as_ref(), destination.as_ref()) {
+        Ok(()) => Ok(false),
+        Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+            move_by_copying(source, destination).map(|()| true)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Makes [rename] fail as if the paths were on different filesystems, so that tests
+    /// reach the fallback of [move_file] without a second filesystem.
+    pub(crate) static IS_CROSSING_DEVICES: std::cell::Cell<bool> =
+        const { std::cell::Cell::new(false) };
+}
+
+fn rename(source: &Path, destination: &Path) -> io::Result<()> {
+    #[cfg(test)]
+    if IS_CROSSING_DEVICES.get() {
+        return Err(io::ErrorKind::CrossesDevices.into());
+    }
+    fs::rename(source, destination)
+}
+
+/// Moves a file by copying it with [copy_file], flushing the copy to the disk, checking that
+/// the copy has the same content as the original and removing the original. If anything fails
+/// before the original is removed, the copy is removed.
+///
+/// # Examples
+/// ```
+/// # use std::fs;
+/// use mass_move_lib::file_operations::move_by_copying;
+///
+/// let directory = tempfile::TempDir::new().unwrap();
+/// let source = directory.path().join("a.txt");
+/// let destination = directory.path().join("b.txt");
+/// fs::write(&source, "hihihihi").unwrap();
+///
+/// move_by_copying(&source, &destination).unwrap();
+///
+/// assert!(!source.exists());
+/// assert_eq!(fs::read_to_string(&destination).unwrap(), "hihihihi");
+/// ```
+pub fn move_by_copying<P: AsRef<Path>, Q: AsRef<Path>>(
+    source: P,
+    destination: Q,
+) -> io::Result<()> {
+    let copy_result = copy_file(&source, &destination).and_then(|()| {
+        File::open(&destination)?.sync_all()?;
+        if !have_same_content(&source, &destination)? {
+            return Err(io::Error::other("copy differs from the original"));
+        }
+        Ok(())
+    });
+    if let Err(err) = copy_result {
+        let _ = fs::remove_file(&destination);
+        return Err(err);
+    }
+    fs::remove_file(source)
+}
+
+/// Returns true if both files have the same length and the same bytes.
+fn have_same_content<P: AsRef<Path>, Q: AsRef<Path>>(path: P, other_path: Q) -> io::Result<bool> {
+    let file = File::open(path)?;
+    let other_file = File::open(other_path)?;
+    if file.metadata()?.len() != other_file.metadata()?.len() {
+        return Ok(false);
+    }
+    let mut reader = BufReader::new(file);
+    let mut other_reader = BufReader::new(other_file);
+    loop {
+        let buffer = reader.fill_buf()?;
+        let other_buffer = other_reader.fill_buf()?;
+        let length = buffer.len().min(other_buffer.len());
+        if length == 0 {
+            return Ok(buffer.is_empty() && other_buffer.is_empty());
+        }
+        if buffer[..length] != other_buffer[..length] {
+            return Ok(false);
+        }
+        reader.consume(length);
+        other_reader.consume(length);
+    }
+}
+
+/// Appends the content of the source file to the end of the destination file. The destination
+/// is created if it doesn't exist.
+///
+/// # Examples
+/// ```
+/// # use std::fs;
+/// use mass_move_lib::file_operations::append_file;
+///
+/// let directory = tempfile::TempDir::new().unwrap();
+/// let part1 = directory.path().join("log_1_part1.txt");
+/// let part2 = directory.path().join("log_1_part2.txt");
+/// let log = directory.path().join("log_1.txt");
+/// fs::write(&part1, "first\n").unwrap();
+/// fs::write(&part2, "second\n").unwrap();
+///
+/// append_file(&part1, &log).unwrap();
+/// append_file(&part2, &log).unwrap();
+///
+/// assert_eq!(fs::read_to_string(&log).unwrap(), "first\nsecond\n");
+/// ```
+pub fn append_file<P: AsRef<Path>, Q: AsRef<Path>>(source: P, destination: Q) -> io::Result<()> {
+    let mut source_file = File::open(source)?;
+    let mut destination_file = File::options()
+        .create(true)
+        .append(true)
+        .open(destination)?;
+    io::copy(&mut source_file, &mut destination_file)?;
+    Ok(())
+}
+
+/// Cuts the file to the given length. It reverts [append_file].
+pub fn truncate_file<P: AsRef<Path>>(path: P, length: u64) -> io::Result<()> {
+    File::options().write(true).open(path)?.set_len(length)
+}
+
+/// Creates a hard link at the destination that points to the same file as the source.
+pub fn hard_link<P: AsRef<Path>, Q: AsRef<Path>>(source: P, destination: Q) -> io::Result<()> {
+    fs::hard_link(source, destination)
+}
+
+/// Creates a symbolic link at the destination that points to the source. With
+/// [SymlinkStyle::Relative] the link contains the path to the source relative to the directory
+/// of the link, with [SymlinkStyle::Absolute] it contains the absolute path of the source.
+/// Both directories must exist.
+///
+/// # Examples
+/// ```
+/// # use std::fs;
+/// use mass_move_lib::file_operations::symlink;
+/// use mass_move_lib::rename_plan::SymlinkStyle;
+///
+/// let directory = tempfile::TempDir::new().unwrap();
+/// fs::create_dir(directory.path().join("data")).unwrap();
+/// fs::create_dir(directory.path().join("view")).unwrap();
+/// let source = directory.path().join("data/a.txt");
+/// let destination = directory.path().join("view/b.txt");
+/// fs::write(&source, "hihihihi").unwrap();
+///
+/// symlink(&source, &destination, SymlinkStyle::Relative).unwrap();
+///
+/// assert_eq!(fs::read_link(&destination).unwrap().to_str(), Some("../data/a.txt"));
+/// assert_eq!(fs::read_to_string(&destination).unwrap(), "hihihihi");
+/// ```
+pub fn symlink<P: AsRef<Path>, Q: AsRef<Path>>(
+    source: P,
+    destination: Q,
+    style: SymlinkStyle,
+) -> io::Result<()> {
+    let absolute_source = absolute_path(source.as_ref())?;
+    let link_content = match style {
+        SymlinkStyle::Absolute => absolute_source,
+        SymlinkStyle::Relative => {
+            let link_directory = absolute_path(destination.as_ref())?
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            relative_path(&link_directory, &absolute_source)
+        }
+    };
+    create_symlink(link_content, destination)
+}
+
+#[cfg(unix)]
+fn create_symlink<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn create_symlink<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
+
+/// Returns the absolute path of a file with symbolic links resolved in its directory but
+/// not in its own name.
+fn absolute_path(path: &Path) -> io::Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    Ok(fs::canonicalize(directory)?.join(file_name))
+}
+
+/// Returns the path that leads from the directory to the target. Both must be absolute.
+fn relative_path(directory: &Path, target: &Path) -> PathBuf {
+    let directory_components: Vec<Component> = directory.components().collect();
+    let target_components: Vec<Component> = target.components().collect();
+    let common_length = directory_components
+        .iter()
+        .zip(&target_components)
+        .take_while(|(directory_component, target_component)| {
+            directory_component == target_component
+        })
+        .count();
+
+    let mut result = PathBuf::new();
+    for _ in common_length..directory_components.len() {
+        result.push("..");
+    }
+    for component in &target_components[common_length..] {
+        result.push(component);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(test)]
+    mod test_copy_file {
+        use crate::file_operations::{copy_file, is_same_file};
+        use std::fs::{self, File, FileTimes};
+        use std::time::{Duration, SystemTime};
+        use tempfile::TempDir;
+
+        #[test]
+        fn test_times_are_preserved() {
+            let temporary_directory = TempDir::new().unwrap();
+            let source = temporary_directory.path().join("a");
+            let destination = temporary_directory.path().join("b");
+            fs::write(&source, "hihihihi").unwrap();
+            let old_time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+            File::options()
+                .write(true)
+                .open(&source)
+                .unwrap()
+                .set_times(FileTimes::new().set_modified(old_time))
+                .unwrap();
+
+            copy_file(&source, &destination).unwrap();
+            assert_eq!(
+                fs::metadata(&destination).unwrap().modified().unwrap(),
+                old_time
+            );
+            assert_eq!(fs::read(&source).unwrap(), fs::read(&destination).unwrap());
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn test_permissions_are_preserved() {
+            use std::os::unix::fs::PermissionsExt;
+
+            let temporary_directory = TempDir::new().unwrap();
+            let source = temporary_directory.path().join("a");
+            let destination = temporary_directory.path().join("b");
+            fs::write(&source, "hihihihi").unwrap();
+            fs::set_permissions(&source, fs::Permissions::from_mode(0o640)).unwrap();
+
+            copy_file(&source, &destination).unwrap();
+            assert_eq!(
+                fs::metadata(&destination).unwrap().permissions().mode() & 0o777,
+                0o640
+            );
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn test_same_file_is_refused() {
+            let temporary_directory = TempDir::new().unwrap();
+            let source = temporary_directory.path().join("a");
+            let symbolic_link = temporary_directory.path().join("b");
+            let hard_link = temporary_directory.path().join("c");
+            fs::write(&source, "hihihihi").unwrap();
+            std::os::unix::fs::symlink(&source, &symbolic_link).unwrap();
+            fs::hard_link(&source, &hard_link).unwrap();
+
+            assert!(copy_file(&source, &symbolic_link).is_err());
+            assert!(copy_file(&source, &hard_link).is_err());
+            assert!(copy_file(&source, &source).is_err());
+            assert_eq!(fs::read_to_string(&source).unwrap(), "hihihihi");
+        }
+
+        #[test]
+        fn test_different_files_are_not_same() {
+            let temporary_directory = TempDir::new().unwrap();
+            let source = temporary_directory.path().join("a");
+            let destination = temporary_directory.path().join("b");
+            fs::write(&source, "hihihihi").unwrap();
+            fs::write(&destination, "old").unwrap();
+
+            assert!(!is_same_file(&source, &destination));
+            assert!(is_same_file(&source, &source));
+            copy_file(&source, &destination).unwrap();
+            assert_eq!(fs::read_to_string(&destination).unwrap(), "hihihihi");
+        }
+
+        #[test]
+        fn test_missing_source() {
+            let temporary_directory = TempDir::new().unwrap();
+            let source = temporary_directory.path().join("a");
+            let destination = temporary_directory.path().join("b");
+            assert!(copy_file(&source, &destination).is_err());
+            assert!(fs::metadata(&destination).is_err());
+        }
+    }
+
+    #[cfg(test)]
+    mod test_move_file {
+        use crate::file_operations::{
+            have_same_content, move_by_copying, move_file, IS_CROSSING_DEVICES,
+        };
+        use std::fs::{self, File, FileTimes};
+        use std::time::{Duration, SystemTime};
+        use tempfile::TempDir;
+
+        #[test]
+        fn test_same_filesystem_is_renamed() {
+            let temporary_directory = TempDir::new().unwrap();
+            let source = temporary_directory.path().join("a");
+            let destination = temporary_directory.path().join("b");
+            fs::write(&source, "hihihihi").unwrap();
+
+            assert!(!move_file(&source, &destination).unwrap());
+            assert!(fs::metadata(&source).is_err());
+            assert_eq!(fs::read_to_string(&destination).unwrap(), "hihihihi");
+        }
+
+        #[test]
+        fn test_move_by_copying_keeps_times() {
+            let temporary_directory = TempDir::new().unwrap();
+            let source = temporary_directory.path().join("a");
+            let destination = temporary_directory.path().join("b");
+            fs::write(&source, "hihihihi").unwrap();
+            let old_time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+            File::options()
+                .write(true)
+                .open(&source)
+                .unwrap()
+                .set_times(FileTimes::new().set_modified(old_time))
+                .unwrap();
+
+            move_by_copying(&source, &destination).unwrap();
+            assert!(fs::metadata(&source).is_err());
+            assert_eq!(
+                fs::metadata(&destination).unwrap().modified().unwrap(),
+                old_time
+            );
+        }
+
+        #[test]
+        fn test_other_filesystem_is_copied() {
+            let temporary_directory = TempDir::new().unwrap();
+            let source = temporary_directory.path().join("a");
+            let destination = temporary_directory.path().join("b");
+            fs::write(&source, "hihihihi").unwrap();
+
+            IS_CROSSING_DEVICES.set(true);
+            let is_copied = move_file(&source, &destination);
+            IS_CROSSING_DEVICES.set(false);
+            assert!(is_copied.unwrap());
+            assert!(fs::metadata(&source).is_err());
+            assert_eq!(fs::read_to_string(&destination).unwrap(), "hihihihi");
+        }
+
+        #[test]
+        fn test_content_comparison() {
+            let temporary_directory = TempDir::new().unwrap();
+            let path = temporary_directory.path().join("a");
+            let same_path = temporary_directory.path().join("b");
+            let other_path = temporary_directory.path().join("c");
+            let content = "hi".repeat(10_000);
+            fs::write(&path, &content).unwrap();
+            fs::write(&same_path, &content).unwrap();
+            fs::write(&other_path, format!("{}ho", &content[2..])).unwrap();
+
+            assert!(have_same_content(&path, &same_path).unwrap());
+            assert!(!have_same_content(&path, &other_path).unwrap());
+            fs::write(&other_path, "hi").unwrap();
+            assert!(!have_same_content(&path, &other_path).unwrap());
+        }
+
+        #[test]
+        fn test_failed_copy_keeps_source() {
+            let temporary_directory = TempDir::new().unwrap();
+            let source = temporary_directory.path().join("a");
+            let destination = temporary_directory.path().join("not_exists/b");
+            fs::write(&source, "hihihihi").unwrap();
+
+            assert!(move_by_copying(&source, &destination).is_err());
+            assert_eq!(fs::read_to_string(&source).unwrap(), "hihihihi");
+        }
+    }
+
+    #[cfg(test)]
+    mod test_relative_path {
+        use crate::file_operations::relative_path;
+        use std::path::Path;
+
+        #[test]
+        fn test_few_cases() {
+            assert_eq!(
+                relative_path(Path::new("/a/b"), Path::new("/a/b/c.txt")),
+                Path::new("c.txt")
+            );
+            assert_eq!(
+                relative_path(Path::new("/a/b"), Path::new("/a/c/d.txt")),
+                Path::new("../c/d.txt")
+            );
+            assert_eq!(
+                relative_path(Path::new("/a/b/c"), Path::new("/d.txt")),
+                Path::new("../../../d.txt")
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[cfg(test)]
+    mod test_links {
+        use crate::file_operations::{hard_link, is_occupied, symlink};
+        use crate::rename_plan::SymlinkStyle;
+        use std::fs;
+        use tempfile::TempDir;
+
+        #[test]
+        fn test_hard_link() {
+            use std::os::unix::fs::MetadataExt;
+
+            let temporary_directory = TempDir::new().unwrap();
+            let source = temporary_directory.path().join("a");
+            let destination = temporary_directory.path().join("b");
+            fs::write(&source, "hihihihi").unwrap();
+
+            hard_link(&source, &destination).unwrap();
+            assert_eq!(
+                fs::metadata(&source).unwrap().ino(),
+                fs::metadata(&destination).unwrap().ino()
+            );
+        }
+
+        #[test]
+        fn test_absolute_symlink() {
+            let temporary_directory = TempDir::new().unwrap();
+            let source = temporary_directory.path().join("a");
+            let destination = temporary_directory.path().join("b");
+            fs::write(&source, "hihihihi").unwrap();
+
+            symlink(&source, &destination, SymlinkStyle::Absolute).unwrap();
+            assert_eq!(
+                fs::read_link(&destination).unwrap(),
+                fs::canonicalize(&source).unwrap()
+            );
+        }
+
+        #[test]
+        fn test_dangling_symlink_is_occupied() {
+            let temporary_directory = TempDir::new().unwrap();
+            let source = temporary_directory.path().join("a");
+            let destination = temporary_directory.path().join("b");
+            fs::write(&source, "hihihihi").unwrap();
+
+            symlink(&source, &destination, SymlinkStyle::Relative).unwrap();
+            fs::remove_file(&source).unwrap();
+            assert!(!destination.exists());
+            assert!(is_occupied(&destination));
+        }
+    }
+}
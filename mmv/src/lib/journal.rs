@@ -0,0 +1,410 @@
+use crate::error::MassMoveError;
+use crate::file_operations::{is_occupied, move_file, truncate_file};
+use crate::rename_plan::Operation;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One line of a journal: an operation on a file that was about to be performed.
+/// `inode` and `size` describe the file that appears at the destination, so that it can be
+/// recognised when the operation is undone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Journals written before other operations appeared contain only moves.
+    #[serde(default)]
+    pub operation: Operation,
+    pub source: String,
+    pub destination: String,
+    /// Seconds since the UNIX epoch.
+    pub timestamp: u64,
+    /// Inode number of the file. It is known only on unix and not for copies and moves
+    /// between filesystems, since a copy gets a new inode.
+    pub inode: Option<u64>,
+    pub size: u64,
+    /// Size of the destination before the file was appended to it. It is [None] for other
+    /// operations and if the destination didn't exist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_size: Option<u64>,
+    /// The entry records that the file at the source, which an earlier entry put there,
+    /// was removed (check [Journal::record_removal]). The destination is the same path.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub removed: bool,
+}
+
+/// A file where every operation on a file is recorded as a line of JSON before it is
+/// performed.
+/// New records are appended to the existing content of the file.
+pub struct Journal {
+    path: PathBuf,
+    file: File,
+}
+
+impl Journal {
+    /// Opens the journal file for appending, creating it if it doesn't exist.
+    pub fn open(path: &Path) -> Result<Self, MassMoveError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|err| journal_error(path, err))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    /// Records the operation on source and destination and makes sure that the record
+    /// reached the disk. Must be called before the operation is performed.
+    pub fn record(
+        &mut self,
+        operation: Operation,
+        source: &str,
+        destination: &str,
+    ) -> Result<(), MassMoveError> {
+        let metadata = fs::metadata(source).map_err(|err| MassMoveError::Io {
+            operation,
+            source: source.to_string(),
+            dest: destination.to_string(),
+            err,
+        })?;
+        let entry = JournalEntry {
+            operation,
+            source: source.to_string(),
+            destination: destination.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+            inode: match operation {
+                Operation::Copy | Operation::Append => None,
+                Operation::Move if !is_same_filesystem(&metadata, destination) => None,
+                _ => inode(&metadata),
+            },
+            size: metadata.len(),
+            previous_size: match operation {
+                Operation::Append => fs::metadata(destination)
+                    .ok()
+                    .map(|metadata| metadata.len()),
+                _ => None,
+            },
+            removed: false,
+        };
+        self.write(&entry)
+    }
+
+    /// Records that the file at the path, which was put there by a recorded move, is about
+    /// to be removed for good. Undo leaves that move alone instead of failing on the missing
+    /// file. Must be called before the file is removed.
+    pub fn record_removal(&mut self, path: &str) -> Result<(), MassMoveError> {
+        let metadata = fs::metadata(path).map_err(|err| journal_error(Path::new(path), err))?;
+        let entry = JournalEntry {
+            operation: Operation::Move,
+            source: path.to_string(),
+            destination: path.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+            inode: inode(&metadata),
+            size: metadata.len(),
+            previous_size: None,
+            removed: true,
+        };
+        self.write(&entry)
+    }
+
+    fn write(&mut self, entry: &JournalEntry) -> Result<(), MassMoveError> {
+        let mut line = serde_json::to_string(entry).unwrap();
+        line.push('\n');
+        self.file
+            .write_all(line.as_bytes())
+            .and_then(|()| self.file.sync_data())
+            .map_err(|err| journal_error(&self.path, err))
+    }
+}
+
+/// Reads all entries of a journal in the order they were recorded.
+pub fn read_journal(path: &Path) -> Result<Vec<JournalEntry>, MassMoveError> {
+    let file = File::open(path).map_err(|err| journal_error(path, err))?;
+    let mut entries: Vec<JournalEntry> = Vec::new();
+    for (line_index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|err| journal_error(path, err))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line).map_err(|_| MassMoveError::InvalidJournal {
+            path: path.to_string_lossy().to_string(),
+            line: line_index + 1,
+        })?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Replays a journal backwards: every moved file is moved from its destination back to
+/// its source, every copy or link is removed and every appended content is cut off.
+/// Before that, the file at the destination is checked to be the one that was put there
+/// (same size and, except copies on unix, same inode; for appending the size must be
+/// the previous size plus the size of the appended file) and the source of a move is checked
+/// to be free.
+/// Records of operations that never happened because they failed are skipped, and so are
+/// moves of files that were removed later (like files replaced in a forced transactional run).
+/// Stops at the first file that doesn't match. Returns the undone entries in undo order.
+///
+/// # Examples
+/// ```
+/// # use std::fs;
+/// # use std::path::Path;
+/// use mass_move_lib::execute::{execute, ExecuteOptions};
+/// use mass_move_lib::journal::undo;
+/// use mass_move_lib::rename_plan::plan;
+///
+/// let directory = tempfile::TempDir::new().unwrap();
+/// let path = directory.path().to_str().unwrap();
+/// fs::write(format!("{}/a.txt", path), "a").unwrap();
+/// let journal = directory.path().join("journal.jsonl");
+///
+/// let rename_plan = plan(&format!("{}/*.txt", path), &format!("{}/#1.bin", path)).unwrap();
+/// let options = ExecuteOptions {
+///     journal: Some(journal.clone()),
+///     ..ExecuteOptions::default()
+/// };
+/// execute(&rename_plan, &options).unwrap();
+/// assert!(Path::new(&format!("{}/a.bin", path)).exists());
+///
+/// let undone = undo(&journal).unwrap();
+/// assert_eq!(undone.len(), 1);
+/// assert!(Path::new(&format!("{}/a.txt", path)).exists());
+/// ```
+pub fn undo(path: &Path) -> Result<Vec<JournalEntry>, MassMoveError> {
+    let mut undone: Vec<JournalEntry> = Vec::new();
+    let mut removed_paths: HashSet<String> = HashSet::new();
+    for entry in read_journal(path)?.into_iter().rev() {
+        if entry.removed {
+            removed_paths.insert(entry.source);
+            continue;
+        }
+        if entry.operation == Operation::Move && removed_paths.remove(&entry.destination) {
+            continue;
+        }
+        let is_undone = match entry.operation {
+            Operation::Append => undo_append(&entry)?,
+            _ => undo_placement(&entry)?,
+        };
+        if is_undone {
+            undone.push(entry);
+        }
+    }
+    Ok(undone)
+}
+
+/// Moves the file back or removes the copy or the link. Returns false if the operation
+/// never happened.
+fn undo_placement(entry: &JournalEntry) -> Result<bool, MassMoveError> {
+    let destination_metadata = fs::metadata(&entry.destination).ok();
+    let source_metadata = fs::metadata(&entry.source).ok();
+    if destination_metadata.is_none()
+        && source_metadata.is_some_and(|metadata| is_journaled_file(entry, &metadata))
+    {
+        return Ok(false);
+    }
+    if !destination_metadata.is_some_and(|metadata| is_journaled_file(entry, &metadata)) {
+        return Err(MassMoveError::JournalMismatch(entry.destination.clone()));
+    }
+
+    if entry.operation != Operation::Move {
+        fs::remove_file(&entry.destination).map_err(|err| MassMoveError::Revert {
+            path: entry.destination.clone(),
+            err,
+        })?;
+        return Ok(true);
+    }
+    if is_occupied(&entry.source) {
+        return Err(MassMoveError::DestinationExists(entry.source.clone()));
+    }
+    move_file(&entry.destination, &entry.source).map_err(|err| MassMoveError::Io {
+        operation: Operation::Move,
+        source: entry.destination.clone(),
+        dest: entry.source.clone(),
+        err,
+    })?;
+    Ok(true)
+}
+
+/// Cuts the appended content off the destination or removes the destination if it was
+/// created by appending. Returns false if the appending never happened.
+fn undo_append(entry: &JournalEntry) -> Result<bool, MassMoveError> {
+    let current_size = fs::metadata(&entry.destination)
+        .ok()
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len());
+    if current_size == entry.previous_size {
+        return Ok(false);
+    }
+    if current_size != Some(entry.previous_size.unwrap_or(0) + entry.size) {
+        return Err(MassMoveError::JournalMismatch(entry.destination.clone()));
+    }
+
+    let result = match entry.previous_size {
+        Some(previous_size) => truncate_file(&entry.destination, previous_size),
+        None => fs::remove_file(&entry.destination),
+    };
+    result.map_err(|err| MassMoveError::Revert {
+        path: entry.destination.clone(),
+        err,
+    })?;
+    Ok(true)
+}
+
+/// Returns true if the metadata describes the same file as the journal entry.
+fn is_journaled_file(entry: &JournalEntry, metadata: &Metadata) -> bool {
+    metadata.is_file()
+        && metadata.len() == entry.size
+        && (entry.inode.is_none() || inode(metadata) == entry.inode)
+}
+
+/// Returns false if the file of the metadata can't be renamed to the destination because
+/// the directory of the destination is on another filesystem.
+#[cfg(unix)]
+fn is_same_filesystem(metadata: &Metadata, destination: &str) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    let directory = match Path::new(destination).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::metadata(directory).map_or(true, |directory_metadata| {
+        directory_metadata.dev() == metadata.dev()
+    })
+}
+
+#[cfg(not(unix))]
+fn is_same_filesystem(_metadata: &Metadata, _destination: &str) -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn inode(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn inode(_metadata: &Metadata) -> Option<u64> {
+    None
+}
+
+fn journal_error(path: &Path, err: std::io::Error) -> MassMoveError {
+    MassMoveError::Journal {
+        path: path.to_string_lossy().to_string(),
+        err,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(test)]
+    mod test_journal {
+        use crate::journal::{read_journal, undo, Journal};
+        use crate::rename_plan::Operation;
+        use std::fs;
+        use tempfile::TempDir;
+
+        #[test]
+        fn test_record_and_read() {
+            let temporary_directory = TempDir::new().unwrap();
+            let directory_path = temporary_directory.path().to_str().unwrap();
+            let journal_path = temporary_directory.path().join("journal");
+            fs::write(format!("{}/a", directory_path), "hihihihi").unwrap();
+
+            let mut journal = Journal::open(&journal_path).unwrap();
+            journal
+                .record(
+                    Operation::Move,
+                    &format!("{}/a", directory_path),
+                    &format!("{}/b", directory_path),
+                )
+                .unwrap();
+            drop(journal);
+            let mut journal = Journal::open(&journal_path).unwrap();
+            journal
+                .record(
+                    Operation::Move,
+                    &format!("{}/a", directory_path),
+                    &format!("{}/c", directory_path),
+                )
+                .unwrap();
+
+            let entries = read_journal(&journal_path).unwrap();
+            assert_eq!(entries.len(), 2);
+            assert_eq!(entries[0].destination, format!("{}/b", directory_path));
+            assert_eq!(entries[1].destination, format!("{}/c", directory_path));
+            assert_eq!(entries[1].size, 8);
+        }
+
+        #[test]
+        fn test_invalid_journal() {
+            let temporary_directory = TempDir::new().unwrap();
+            let journal_path = temporary_directory.path().join("journal");
+            fs::write(&journal_path, "\nnot json\n").unwrap();
+            assert!(read_journal(&journal_path).is_err());
+            assert!(read_journal(&temporary_directory.path().join("not_exists")).is_err());
+        }
+
+        #[test]
+        fn test_old_entries_are_moves() {
+            let temporary_directory = TempDir::new().unwrap();
+            let journal_path = temporary_directory.path().join("journal");
+            fs::write(
+                &journal_path,
+                r#"{"source":"a","destination":"b","timestamp":0,"inode":null,"size":0}"#,
+            )
+            .unwrap();
+            assert_eq!(
+                read_journal(&journal_path).unwrap()[0].operation,
+                Operation::Move
+            );
+        }
+
+        #[test]
+        fn test_undo_refuses_changed_files() {
+            let temporary_directory = TempDir::new().unwrap();
+            let directory_path = temporary_directory.path().to_str().unwrap();
+            let journal_path = temporary_directory.path().join("journal");
+            let source = format!("{}/a", directory_path);
+            let destination = format!("{}/b", directory_path);
+            fs::write(&source, "hihihihi").unwrap();
+
+            let mut journal = Journal::open(&journal_path).unwrap();
+            journal
+                .record(Operation::Move, &source, &destination)
+                .unwrap();
+            fs::rename(&source, &destination).unwrap();
+            fs::write(&destination, "changed").unwrap();
+
+            assert!(undo(&journal_path).is_err());
+            assert!(fs::metadata(&source).is_err());
+        }
+
+        #[test]
+        fn test_undo_skips_failed_moves() {
+            let temporary_directory = TempDir::new().unwrap();
+            let directory_path = temporary_directory.path().to_str().unwrap();
+            let journal_path = temporary_directory.path().join("journal");
+            let source = format!("{}/a", directory_path);
+            fs::write(&source, "hihihihi").unwrap();
+
+            let mut journal = Journal::open(&journal_path).unwrap();
+            journal
+                .record(
+                    Operation::Move,
+                    &source,
+                    &format!("{}/not_exists/b", directory_path),
+                )
+                .unwrap();
+
+            assert_eq!(undo(&journal_path).unwrap(), vec![]);
+            assert!(fs::metadata(&source).is_ok());
+        }
+    }
+}
@@ -1,4 +1,12 @@
 pub mod build_by_target_pattern;
+pub mod case_modifier;
+pub mod error;
+pub mod execute;
+pub mod file_operations;
+pub mod journal;
 pub mod mass_move;
+pub mod path_pattern;
+pub mod rename_plan;
+pub mod schedule;
 pub mod search_by_pattern;
 pub mod string_pattern;
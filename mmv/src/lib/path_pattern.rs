@@ -0,0 +1,200 @@
+use crate::error::MassMoveError;
+use crate::string_pattern::StringPattern;
+
+/// A pattern of a directory in a [PathPattern].
+pub enum DirectoryPattern {
+    /// A directory name without wildcards, it is used as is.
+    Literal(String),
+    /// Any directory whose name matches the [StringPattern].
+    Wildcard(StringPattern),
+    /// '**': any number of directory levels, including none. The path of the matched
+    /// directories relative to the previous ones is a single substring, like 'a/b' or ''.
+    Recursive,
+}
+
+/// A pattern of full file names: '/'-separated patterns of directories followed by
+/// a [StringPattern] of the file name. Every component may contain wildcards.
+/// Substrings hidden behind wildcards are numbered from left to right through all
+/// components, so in 'projects/*/build/*.log' the project name is the first one.
+/// A directory component that is exactly '**' matches any number of nested directories.
+pub struct PathPattern {
+    pub directories: Vec<DirectoryPattern>,
+    pub file_name: StringPattern,
+}
+
+impl PathPattern {
+    /// Returns new [PathPattern] formed by a string or [MassMoveError::InvalidSourcePattern]
+    /// if the string has no '/' or any of its components can't be compiled.
+    ///
+    /// # Examples
+    /// ```
+    /// use mass_move_lib::path_pattern::{DirectoryPattern, PathPattern};
+    ///
+    /// let pattern = PathPattern::try_new("projects/*/build/*.log").unwrap();
+    ///
+    /// assert_eq!(pattern.directories.len(), 3);
+    /// assert!(matches!(&pattern.directories[0], DirectoryPattern::Literal(name) if name == "projects"));
+    /// assert!(matches!(&pattern.directories[1], DirectoryPattern::Wildcard(_)));
+    /// assert!(pattern.file_name.is_matches("debug.log"));
+    /// ```
+    pub fn try_new(raw_path_pattern: &str) -> Result<Self, MassMoveError> {
+        Self::parse(raw_path_pattern, StringPattern::try_new)
+    }
+
+    /// Does the same as [PathPattern::try_new] but the file name part is a regular expression
+    /// (check [StringPattern::from_regex]). Directories still use wildcards.
+    pub fn try_new_regex(raw_path_pattern: &str) -> Result<Self, MassMoveError> {
+        Self::parse(raw_path_pattern, StringPattern::from_regex)
+    }
+
+    /// Returns [PathPattern] for the same pattern that matches names of files and directories
+    /// regardless of their case (check [StringPattern::ignoring_case]). Directories without
+    /// wildcards are searched for too unless their names have no letters.
+    pub fn ignoring_case(self) -> Self {
+        let directories = self
+            .directories
+            .into_iter()
+            .map(|directory| match directory {
+                DirectoryPattern::Literal(name) if name.to_lowercase() != name.to_uppercase() => {
+                    DirectoryPattern::Wildcard(StringPattern::from_literal(&name).ignoring_case())
+                }
+                DirectoryPattern::Wildcard(pattern) => {
+                    DirectoryPattern::Wildcard(pattern.ignoring_case())
+                }
+                directory => directory,
+            })
+            .collect();
+        Self {
+            directories,
+            file_name: self.file_name.ignoring_case(),
+        }
+    }
+
+    /// Returns the names of all substrings of the pattern in order, [None] for substrings
+    /// without a name.
+    pub fn capture_names(&self) -> Vec<Option<String>> {
+        let mut capture_names: Vec<Option<String>> = Vec::new();
+        for directory in &self.directories {
+            match directory {
+                DirectoryPattern::Literal(_) => {}
+                DirectoryPattern::Wildcard(pattern) => {
+                    capture_names.extend(pattern.capture_names())
+                }
+                DirectoryPattern::Recursive => capture_names.push(None),
+            }
+        }
+        capture_names.extend(self.file_name.capture_names());
+        capture_names
+    }
+
+    fn parse(
+        raw_path_pattern: &str,
+        parse_file_name: fn(&str) -> Result<StringPattern, MassMoveError>,
+    ) -> Result<Self, MassMoveError> {
+        let invalid_pattern = || MassMoveError::InvalidSourcePattern(raw_path_pattern.to_string());
+        let Some((raw_directories, raw_file_name)) = raw_path_pattern.rsplit_once('/') else {
+            return Err(invalid_pattern());
+        };
+
+        let mut directories: Vec<DirectoryPattern> = Vec::new();
+        for raw_directory in raw_directories.split('/') {
+            if raw_directory == "**" {
+                directories.push(DirectoryPattern::Recursive);
+                continue;
+            }
+            let pattern = StringPattern::try_new(raw_directory).map_err(|_| invalid_pattern())?;
+            directories.push(match pattern.literal() {
+                Some(name) => DirectoryPattern::Literal(name),
+                None => DirectoryPattern::Wildcard(pattern),
+            });
+        }
+        let file_name = parse_file_name(raw_file_name).map_err(|_| invalid_pattern())?;
+        Ok(Self {
+            directories,
+            file_name,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(test)]
+    mod test_try_new {
+        use crate::path_pattern::{DirectoryPattern, PathPattern};
+
+        #[test]
+        fn test_without_directory() {
+            assert!(PathPattern::try_new("*.txt").is_err());
+            assert!(PathPattern::try_new("").is_err());
+        }
+
+        #[test]
+        fn test_recursive() {
+            let pattern = PathPattern::try_new("src/**/**").unwrap();
+            assert!(matches!(
+                pattern.directories.as_slice(),
+                [DirectoryPattern::Literal(_), DirectoryPattern::Recursive]
+            ));
+            assert_eq!(
+                pattern.file_name.get_generic_parts("ab").unwrap(),
+                vec!["", "ab"]
+            );
+        }
+
+        #[test]
+        fn test_regex() {
+            let pattern = PathPattern::try_new_regex(r"*/(?<name>\w+)\.(txt|md)").unwrap();
+            assert!(pattern.file_name.is_matches("notes.md"));
+            assert!(!pattern.file_name.is_matches("notes.md.bak"));
+            assert_eq!(
+                pattern.capture_names(),
+                vec![None, Some("name".to_string()), None]
+            );
+            assert!(PathPattern::try_new_regex("dir/(unclosed").is_err());
+        }
+
+        #[test]
+        fn test_ignoring_case() {
+            let pattern = PathPattern::try_new("/tmp/../Photos/*.jpg")
+                .unwrap()
+                .ignoring_case();
+            assert!(matches!(
+                pattern.directories.as_slice(),
+                [
+                    DirectoryPattern::Literal(root),
+                    DirectoryPattern::Wildcard(tmp),
+                    DirectoryPattern::Literal(parent),
+                    DirectoryPattern::Wildcard(photos)
+                ] if root.is_empty() && tmp.is_matches("TMP") && parent == ".."
+                    && photos.is_matches("photos") && !photos.is_matches("photo")
+            ));
+            assert!(pattern.file_name.is_matches("a.JPG"));
+            assert!(pattern.capture_names().len() == 1);
+        }
+
+        #[test]
+        fn test_absolute() {
+            let pattern = PathPattern::try_new("/tmp/*.txt").unwrap();
+            assert!(matches!(
+                pattern.directories.as_slice(),
+                [DirectoryPattern::Literal(root), DirectoryPattern::Literal(tmp)]
+                    if root.is_empty() && tmp == "tmp"
+            ));
+        }
+
+        #[test]
+        fn test_wildcards_in_directories() {
+            let pattern = PathPattern::try_new("./a*/b/*c/d.txt").unwrap();
+            assert!(matches!(
+                pattern.directories.as_slice(),
+                [
+                    DirectoryPattern::Literal(_),
+                    DirectoryPattern::Wildcard(_),
+                    DirectoryPattern::Literal(_),
+                    DirectoryPattern::Wildcard(_)
+                ]
+            ));
+            assert_eq!(pattern.file_name.literal(), Some("d.txt".to_string()));
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use crate::build_by_target_pattern::build_by_named_target_pattern;
+use crate::error::MassMoveError;
+use crate::file_operations::is_occupied;
+use crate::path_pattern::PathPattern;
+use crate::schedule::normalize_path;
+use crate::search_by_pattern::{search_files_by_path_pattern, SearchOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// What is done with every matched file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    /// The file is moved to its destination.
+    #[default]
+    Move,
+    /// The file is duplicated at its destination with the same permissions and timestamps.
+    Copy,
+    /// A hard link to the file is created at its destination.
+    HardLink,
+    /// A symbolic link to the file is created at its destination.
+    Symlink(SymlinkStyle),
+    /// The content of the file is appended to its destination. Several files may be appended
+    /// to the same destination in the order of plan entries.
+    Append,
+}
+
+/// How a symbolic link refers to the file it points to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymlinkStyle {
+    /// The link contains the path to the file relative to the directory of the link.
+    #[default]
+    Relative,
+    /// The link contains the absolute path to the file.
+    Absolute,
+}
+
+impl Operation {
+    /// Returns the verb that describes the operation in messages.
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Operation::Move => "move",
+            Operation::Copy => "copy",
+            Operation::HardLink => "hard link",
+            Operation::Symlink(_) => "symlink",
+            Operation::Append => "append",
+        }
+    }
+}
+
+/// A single planned move: full name of the file to move, full name it will get and
+/// substrings of the source path that are hidden behind '*' symbols of the source pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenameEntry {
+    pub source: String,
+    pub destination: String,
+    pub captures: Vec<String>,
+}
+
+impl RenameEntry {
+    /// Returns true if the source and the destination are the same path, so there is
+    /// nothing to do with the file.
+    pub fn is_unchanged(&self) -> bool {
+        normalize_path(&self.source) == normalize_path(&self.destination)
+    }
+}
+
+/// A reason why a [RenamePlan] can't be executed as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    /// The destination file already exists and would be replaced.
+    DestinationExists(String),
+    /// The destination is the source of another entry, which would be replaced or changed
+    /// before its own turn when files are not moved. It can't be forced.
+    DestinationIsSource(String),
+    /// Several sources would be moved to the same destination, so all of them but the last
+    /// would be lost. It can't be forced.
+    DuplicateDestination {
+        destination: String,
+        sources: Vec<String>,
+    },
+}
+
+impl Conflict {
+    /// Returns true if the conflict can be ignored by replacing existing files.
+    pub fn is_forceable(&self) -> bool {
+        matches!(self, Conflict::DestinationExists(_))
+    }
+}
+
+impl From<Conflict> for MassMoveError {
+    fn from(conflict: Conflict) -> Self {
+        match conflict {
+            Conflict::DestinationExists(path) => MassMoveError::DestinationExists(path),
+            Conflict::DestinationIsSource(path) => MassMoveError::DestinationIsSource(path),
+            Conflict::DuplicateDestination {
+                destination,
+                sources,
+            } => MassMoveError::DuplicateDestination {
+                destination,
+                sources,
+            },
+        }
+    }
+}
+
+/// A list of moves computed by [plan]. It can be inspected, filtered or reordered before
+/// being executed by [crate::execute::execute]. Entries are executed in the order they are
+/// stored unless one of them has to wait for another (check [crate::schedule::schedule_moves]).
+/// The operation says what is done with every file, [plan] always makes a plan of moving.
+/// A plan can be saved with serde and executed later.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenamePlan {
+    pub operation: Operation,
+    pub entries: Vec<RenameEntry>,
+    /// Directories of destinations that don't exist yet, parents before their children.
+    /// They are created only if [crate::execute::ExecuteOptions::create_directories] is set.
+    pub directories: Vec<String>,
+}
+
+impl RenamePlan {
+    /// Returns all conflicts the plan would hit if it was executed right now: existing
+    /// destination files and destinations shared by several sources. When moving,
+    /// destinations that are sources of the plan themselves are not conflicts because they
+    /// are moved away first. Otherwise such destinations are [Conflict::DestinationIsSource],
+    /// since the source would be replaced or changed before it is used. Entries with the same
+    /// source and destination are never conflicts.
+    ///
+    /// When appending, existing and shared destinations are expected, so only destinations
+    /// that are sources of the plan themselves are conflicts.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let sources: HashSet<PathBuf> = self
+            .entries
+            .iter()
+            .map(|entry| normalize_path(&entry.source))
+            .collect();
+        let changed_entries = self.entries.iter().filter(|entry| !entry.is_unchanged());
+        if self.operation == Operation::Move {
+            let mut conflicts: Vec<Conflict> = changed_entries
+                .filter(|entry| !sources.contains(&normalize_path(&entry.destination)))
+                .filter(|entry| is_occupied(&entry.destination))
+                .map(|entry| Conflict::DestinationExists(entry.destination.clone()))
+                .collect();
+            conflicts.extend(self.duplicate_destinations());
+            return conflicts;
+        }
+
+        let mut conflicts: Vec<Conflict> = Vec::new();
+        for entry in changed_entries {
+            if sources.contains(&normalize_path(&entry.destination)) {
+                conflicts.push(Conflict::DestinationIsSource(entry.destination.clone()));
+            } else if self.operation != Operation::Append && is_occupied(&entry.destination) {
+                conflicts.push(Conflict::DestinationExists(entry.destination.clone()));
+            }
+        }
+        if self.operation == Operation::Append {
+            return conflicts;
+        }
+        conflicts.extend(self.duplicate_destinations());
+        conflicts
+    }
+
+    /// Returns a [Conflict::DuplicateDestination] for every destination that several
+    /// entries share, in order of the first entry moved there.
+    fn duplicate_destinations(&self) -> Vec<Conflict> {
+        let mut sources_by_destination: HashMap<PathBuf, Vec<&RenameEntry>> = HashMap::new();
+        let mut destinations_order: Vec<PathBuf> = Vec::new();
+        for entry in &self.entries {
+            let destination = normalize_path(&entry.destination);
+            let sources = sources_by_destination
+                .entry(destination.clone())
+                .or_default();
+            if sources.is_empty() {
+                destinations_order.push(destination);
+            }
+            sources.push(entry);
+        }
+
+        destinations_order
+            .iter()
+            .map(|destination| &sources_by_destination[destination])
+            .filter(|entries| entries.len() > 1)
+            .map(|entries| Conflict::DuplicateDestination {
+                destination: entries[0].destination.clone(),
+                sources: entries.iter().map(|entry| entry.source.clone()).collect(),
+            })
+            .collect()
+    }
+}
+
+/// Options that change how [plan_with_options] finds files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlanOptions {
+    /// Limits of '**' components of the source pattern.
+    pub search: SearchOptions,
+    /// The file name part of the source pattern is a regular expression
+    /// (check [PathPattern::try_new_regex]).
+    pub regex: bool,
+    /// Names of files and directories match the source pattern regardless of their case
+    /// (check [PathPattern::ignoring_case]).
+    pub ignore_case: bool,
+}
+
+/// Takes all files that match source pattern (check [PathPattern]) and computes the new full
+/// file name of each of them according to the target pattern. Placeholders are substituted
+/// in the whole target path, so files can be spread into directories named after captures
+/// (check [RenamePlan::directories]). The filesystem is only read.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs::File;
+/// use mass_move_lib::rename_plan::plan;
+///
+/// let directory = tempfile::TempDir::new().unwrap();
+/// let path = directory.path().to_str().unwrap();
+/// File::create(format!("{}/a.txt", path)).unwrap();
+/// File::create(format!("{}/b.txt", path)).unwrap();
+///
+/// let rename_plan = plan(&format!("{}/*.txt", path), &format!("{}/#1.bin", path)).unwrap();
+///
+/// assert_eq!(rename_plan.entries.len(), 2);
+/// assert_eq!(rename_plan.entries[0].source, format!("{}/a.txt", path));
+/// assert_eq!(rename_plan.entries[0].destination, format!("{}/a.bin", path));
+/// assert_eq!(rename_plan.entries[0].captures, vec!["a".to_string()]);
+/// assert!(rename_plan.conflicts().is_empty());
+/// ```
+pub fn plan(source_pattern: &str, target_pattern: &str) -> Result<RenamePlan, MassMoveError> {
+    plan_with_options(source_pattern, target_pattern, &PlanOptions::default())
+}
+
+/// Does the same as [plan] but with given options.
+pub fn plan_with_options(
+    source_pattern: &str,
+    target_pattern: &str,
+    options: &PlanOptions,
+) -> Result<RenamePlan, MassMoveError> {
+    let mut source_path_pattern = if options.regex {
+        PathPattern::try_new_regex(source_pattern)?
+    } else {
+        PathPattern::try_new(source_pattern)?
+    };
+    if options.ignore_case {
+        source_path_pattern = source_path_pattern.ignoring_case();
+    }
+    if !target_pattern.contains('/') {
+        return Err(MassMoveError::InvalidTargetPattern(
+            target_pattern.to_string(),
+        ));
+    }
+
+    let found_files = search_files_by_path_pattern(&source_path_pattern, &options.search);
+    let Some(found_files) = found_files else {
+        return Err(MassMoveError::NoMatches(source_pattern.to_string()));
+    };
+
+    let capture_names = source_path_pattern.capture_names();
+    let mut entries: Vec<RenameEntry> = Vec::new();
+    for found_file in found_files {
+        let captures: Vec<&str> = found_file.captures.iter().map(String::as_str).collect();
+        entries.push(RenameEntry {
+            destination: build_by_named_target_pattern(
+                &captures,
+                &capture_names,
+                target_pattern,
+                "#",
+            )?,
+            source: found_file.path,
+            captures: found_file.captures,
+        });
+    }
+    Ok(RenamePlan {
+        operation: Operation::Move,
+        directories: missing_directories(&entries),
+        entries,
+    })
+}
+
+/// Returns every directory that would have to be created to put files at the destinations
+/// of entries, parents before their children. Paths that end with '..' are never created,
+/// only the directories they go through.
+pub fn missing_directories(entries: &[RenameEntry]) -> Vec<String> {
+    let mut directories: BTreeSet<PathBuf> = BTreeSet::new();
+    for entry in entries {
+        let ancestors = Path::new(&entry.destination).ancestors().skip(1);
+        for directory in ancestors {
+            if directory.as_os_str().is_empty() || is_occupied(directory) {
+                break;
+            }
+            if directory.file_name().is_none() {
+                continue;
+            }
+            directories.insert(directory.components().collect());
+        }
+    }
+    directories
+        .into_iter()
+        .map(|directory| directory.to_string_lossy().to_string())
+        .collect()
+}
@@ -0,0 +1,237 @@
+use crate::file_operations::is_occupied;
+use crate::rename_plan::RenamePlan;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A single filesystem move. Unlike [crate::rename_plan::RenameEntry] it may lead to
+/// a temporary name that is needed to break a cycle of moves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub source: String,
+    pub destination: String,
+}
+
+/// Returns the path without repeated separators and '.' components, so that different
+/// spellings of the same path compare equal.
+pub fn normalize_path(path: &str) -> PathBuf {
+    Path::new(path).components().collect()
+}
+
+/// Orders the moves of a plan so that no file is replaced before it is moved away itself.
+/// Entries whose destination is the source of another entry form chains, which are executed
+/// from the end, and cycles, which are broken by moving one file of the cycle to a temporary
+/// name first. Entries that don't change the path are skipped.
+///
+/// The plan must not have duplicate destinations (check [RenamePlan::conflicts]).
+///
+/// # Examples
+/// ```
+/// use mass_move_lib::rename_plan::{RenameEntry, RenamePlan};
+/// use mass_move_lib::schedule::schedule_moves;
+///
+/// let entry = |source: &str, destination: &str| RenameEntry {
+///     source: source.to_string(),
+///     destination: destination.to_string(),
+///     captures: vec![],
+/// };
+/// let plan = RenamePlan {
+///     entries: vec![entry("dir/1", "dir/2"), entry("dir/2", "dir/3")],
+///     ..RenamePlan::default()
+/// };
+///
+/// let steps = schedule_moves(&plan);
+/// assert_eq!(steps[0].source, "dir/2");
+/// assert_eq!(steps[1].source, "dir/1");
+/// ```
+pub fn schedule_moves(plan: &RenamePlan) -> Vec<Step> {
+    let entries: Vec<usize> = (0..plan.entries.len())
+        .filter(|&index| !plan.entries[index].is_unchanged())
+        .collect();
+
+    let index_by_source: HashMap<PathBuf, usize> = entries
+        .iter()
+        .map(|&index| (normalize_path(&plan.entries[index].source), index))
+        .collect();
+    // next[i] is the entry that has to vacate the destination of entry i before i is moved.
+    let next: HashMap<usize, usize> = entries
+        .iter()
+        .filter_map(|&index| {
+            let destination = normalize_path(&plan.entries[index].destination);
+            index_by_source
+                .get(&destination)
+                .map(|&next_index| (index, next_index))
+        })
+        .collect();
+    let has_previous: HashSet<usize> = next.values().copied().collect();
+
+    let mut steps: Vec<Step> = Vec::new();
+    let mut visited: HashSet<usize> = HashSet::new();
+    let step = |index: usize| Step {
+        source: plan.entries[index].source.clone(),
+        destination: plan.entries[index].destination.clone(),
+    };
+
+    for &head in entries.iter().filter(|index| !has_previous.contains(index)) {
+        let mut chain: Vec<usize> = vec![head];
+        visited.insert(head);
+        while let Some(&next_index) = next.get(chain.last().unwrap()) {
+            chain.push(next_index);
+            visited.insert(next_index);
+        }
+        steps.extend(chain.into_iter().rev().map(step));
+    }
+
+    let used_paths: HashSet<PathBuf> = plan
+        .entries
+        .iter()
+        .flat_map(|entry| [&entry.source, &entry.destination])
+        .map(|path| normalize_path(path))
+        .collect();
+    let mut temporary_names_count: usize = 0;
+    for &start in &entries {
+        if visited.contains(&start) {
+            continue;
+        }
+        let mut cycle: Vec<usize> = vec![start];
+        visited.insert(start);
+        while let Some(&next_index) = next.get(cycle.last().unwrap()) {
+            if next_index == start {
+                break;
+            }
+            cycle.push(next_index);
+            visited.insert(next_index);
+        }
+
+        let temporary_name = make_temporary_name(
+            &plan.entries[start].source,
+            "mmv-tmp",
+            &used_paths,
+            &mut temporary_names_count,
+        );
+        steps.push(Step {
+            source: plan.entries[start].source.clone(),
+            destination: temporary_name.clone(),
+        });
+        steps.extend(cycle[1..].iter().rev().map(|&index| step(index)));
+        steps.push(Step {
+            source: temporary_name,
+            destination: plan.entries[start].destination.clone(),
+        });
+    }
+    steps
+}
+
+/// Returns a hidden name next to the given file that ends with the given suffix and a number
+/// and neither exists nor is among used paths.
+pub(crate) fn make_temporary_name(
+    full_file_name: &str,
+    suffix: &str,
+    used_paths: &HashSet<PathBuf>,
+    temporary_names_count: &mut usize,
+) -> String {
+    let (directory, file_name) = full_file_name
+        .rsplit_once('/')
+        .unwrap_or((".", full_file_name));
+    loop {
+        *temporary_names_count += 1;
+        let temporary_name = format!(
+            "{}/.{}.{}{}",
+            directory, file_name, suffix, temporary_names_count
+        );
+        if !is_occupied(&temporary_name) && !used_paths.contains(&normalize_path(&temporary_name)) {
+            return temporary_name;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(test)]
+    mod test_schedule_moves {
+        use crate::rename_plan::{RenameEntry, RenamePlan};
+        use crate::schedule::{schedule_moves, Step};
+
+        fn make_plan(moves: &[(&str, &str)]) -> RenamePlan {
+            RenamePlan {
+                entries: moves
+                    .iter()
+                    .map(|(source, destination)| RenameEntry {
+                        source: source.to_string(),
+                        destination: destination.to_string(),
+                        captures: vec![],
+                    })
+                    .collect(),
+                ..RenamePlan::default()
+            }
+        }
+
+        fn make_steps(moves: &[(&str, &str)]) -> Vec<Step> {
+            moves
+                .iter()
+                .map(|(source, destination)| Step {
+                    source: source.to_string(),
+                    destination: destination.to_string(),
+                })
+                .collect()
+        }
+
+        #[test]
+        fn test_independent() {
+            let plan = make_plan(&[("d/a", "d/x"), ("d/b", "d/y")]);
+            assert_eq!(
+                schedule_moves(&plan),
+                make_steps(&[("d/a", "d/x"), ("d/b", "d/y")])
+            );
+        }
+
+        #[test]
+        fn test_same_names_are_skipped() {
+            let plan = make_plan(&[("d/a", "d/a"), ("d//b", "d/./b")]);
+            assert_eq!(schedule_moves(&plan), vec![]);
+        }
+
+        #[test]
+        fn test_chain() {
+            let plan = make_plan(&[("d/1", "d/2"), ("d/2", "d/3"), ("d/3", "d/4")]);
+            assert_eq!(
+                schedule_moves(&plan),
+                make_steps(&[("d/3", "d/4"), ("d/2", "d/3"), ("d/1", "d/2")])
+            );
+        }
+
+        #[test]
+        fn test_swap() {
+            let plan = make_plan(&[("d/a", "d/b"), ("d/b", "d/a")]);
+            assert_eq!(
+                schedule_moves(&plan),
+                make_steps(&[
+                    ("d/a", "d/.a.mmv-tmp1"),
+                    ("d/b", "d/a"),
+                    ("d/.a.mmv-tmp1", "d/b")
+                ])
+            );
+        }
+
+        #[test]
+        fn test_cycle_and_chain() {
+            let plan = make_plan(&[
+                ("d/1", "d/2"),
+                ("d/2", "d/3"),
+                ("d/3", "d/1"),
+                ("d/x", "d/y"),
+                ("d/y", "d/z"),
+            ]);
+            assert_eq!(
+                schedule_moves(&plan),
+                make_steps(&[
+                    ("d/y", "d/z"),
+                    ("d/x", "d/y"),
+                    ("d/1", "d/.1.mmv-tmp1"),
+                    ("d/3", "d/1"),
+                    ("d/2", "d/3"),
+                    ("d/.1.mmv-tmp1", "d/2"),
+                ])
+            );
+        }
+    }
+}
@@ -1,7 +1,29 @@
+use crate::path_pattern::{DirectoryPattern, PathPattern};
 use crate::string_pattern::StringPattern;
-use std::fs;
+use std::collections::HashSet;
+use std::fs::{self, DirEntry};
+use std::path::PathBuf;
 use std::string::String;
 
+/// Options of [search_files_by_path_pattern] that limit '**' components of patterns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// How many levels of directories a '**' component matches at most. Unlimited if [None].
+    pub max_depth: Option<usize>,
+    /// Descend into symbolic links to directories while matching '**'. Every directory is
+    /// visited once, so cycles of links are not a problem.
+    pub follow_symlinks: bool,
+}
+
+/// A file found by [search_files_by_path_pattern].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundFile {
+    /// Full name of the file.
+    pub path: String,
+    /// Substrings hidden behind wildcards of all components of the pattern, from left to right.
+    pub captures: Vec<String>,
+}
+
 /// Searches file names in a given directory that match a given [StringPattern]
 ///
 /// # Examples
@@ -29,11 +51,219 @@ use std::string::String;
 pub fn search_file_names_by_pattern(
     path_to_directory: &str,
     pattern_file_name: &StringPattern,
+) -> Option<Vec<String>> {
+    search_names_by_pattern(path_to_directory, pattern_file_name, |entry| {
+        entry.file_type().is_ok_and(|file_type| file_type.is_file())
+    })
+}
+
+/// Searches files whose full names match a given [PathPattern]. Directories of the pattern
+/// that contain wildcards are searched one level at a time, symbolic links to directories
+/// are followed. '**' components walk the whole subtree according to the options.
+/// Found files are sorted by their directories and then by their names.
+/// A file that several '**' components can reach in different ways is found once, with
+/// the captures of the way where earlier '**' components take fewer directories.
+/// Returns [None] if nothing is found.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fs;
+/// use mass_move_lib::path_pattern::PathPattern;
+/// use mass_move_lib::search_by_pattern::{search_files_by_path_pattern, SearchOptions};
+///
+/// let directory = tempfile::TempDir::new().unwrap();
+/// let path = directory.path().to_str().unwrap();
+/// fs::create_dir_all(format!("{}/projects/app/build", path)).unwrap();
+/// fs::write(format!("{}/projects/app/build/debug.log", path), "").unwrap();
+///
+/// let pattern = PathPattern::try_new(&format!("{}/projects/*/build/*.log", path)).unwrap();
+/// let found = search_files_by_path_pattern(&pattern, &SearchOptions::default()).unwrap();
+/// assert_eq!(found[0].path, format!("{}/projects/app/build/debug.log", path));
+/// assert_eq!(found[0].captures, vec!["app".to_string(), "debug".to_string()]);
+///
+/// let pattern = PathPattern::try_new(&format!("{}/**/*.log", path)).unwrap();
+/// let found = search_files_by_path_pattern(&pattern, &SearchOptions::default()).unwrap();
+/// assert_eq!(found[0].captures, vec!["projects/app/build".to_string(), "debug".to_string()]);
+/// ```
+pub fn search_files_by_path_pattern(
+    path_pattern: &PathPattern,
+    options: &SearchOptions,
+) -> Option<Vec<FoundFile>> {
+    // Directories are None until the first component is joined, so that relative patterns
+    // don't get a "./" prefix.
+    let mut directories: Vec<(Option<String>, Vec<String>)> = vec![(None, Vec::new())];
+    for directory_pattern in &path_pattern.directories {
+        let mut next_directories: Vec<(Option<String>, Vec<String>)> = Vec::new();
+        for (directory, captures) in directories {
+            match directory_pattern {
+                DirectoryPattern::Literal(name) => {
+                    next_directories.push((Some(join_path(directory.as_deref(), name)), captures))
+                }
+                DirectoryPattern::Wildcard(pattern) => {
+                    let names = search_names_by_pattern(
+                        readable_path(directory.as_deref()),
+                        pattern,
+                        |entry| entry.path().is_dir(),
+                    );
+                    for name in names.unwrap_or_default() {
+                        let mut name_captures = captures.clone();
+                        name_captures.extend(generic_parts(pattern, &name));
+                        next_directories
+                            .push((Some(join_path(directory.as_deref(), &name)), name_captures));
+                    }
+                }
+                DirectoryPattern::Recursive => {
+                    for (subdirectory, relative_path) in search_subdirectories(directory, options) {
+                        let mut subdirectory_captures = captures.clone();
+                        subdirectory_captures.push(relative_path);
+                        next_directories.push((subdirectory, subdirectory_captures));
+                    }
+                }
+            }
+        }
+        directories = next_directories;
+    }
+
+    let mut found_paths: HashSet<String> = HashSet::new();
+    let mut found_files: Vec<FoundFile> = Vec::new();
+    for (directory, captures) in directories {
+        let file_names = search_file_names_by_pattern(
+            readable_path(directory.as_deref()),
+            &path_pattern.file_name,
+        );
+        for file_name in file_names.unwrap_or_default() {
+            let path = join_path(directory.as_deref(), &file_name);
+            if !found_paths.insert(path.clone()) {
+                continue;
+            }
+            let mut file_captures = captures.clone();
+            file_captures.extend(generic_parts(&path_pattern.file_name, &file_name));
+            found_files.push(FoundFile {
+                path,
+                captures: file_captures,
+            });
+        }
+    }
+    if found_files.is_empty() {
+        return None;
+    }
+    Some(found_files)
+}
+
+/// Returns the directory itself and all its subdirectories down to the maximum depth,
+/// each with its path relative to the directory. Parents go before their children.
+fn search_subdirectories(
+    directory: Option<String>,
+    options: &SearchOptions,
+) -> Vec<(Option<String>, String)> {
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    if options.follow_symlinks {
+        visited.extend(fs::canonicalize(readable_path(directory.as_deref())).ok());
+    }
+    let mut subdirectories: Vec<(Option<String>, String)> = Vec::new();
+    collect_subdirectories(
+        directory,
+        String::new(),
+        0,
+        options,
+        &mut visited,
+        &mut subdirectories,
+    );
+    subdirectories
+}
+
+fn collect_subdirectories(
+    directory: Option<String>,
+    relative_path: String,
+    depth: usize,
+    options: &SearchOptions,
+    visited: &mut HashSet<PathBuf>,
+    subdirectories: &mut Vec<(Option<String>, String)>,
+) {
+    let is_deepest = options
+        .max_depth
+        .is_some_and(|max_depth| depth >= max_depth);
+    let mut names: Vec<String> = Vec::new();
+    if !is_deepest {
+        if let Ok(directory_entries) = fs::read_dir(readable_path(directory.as_deref())) {
+            names = directory_entries
+                .flatten()
+                .filter(|entry| {
+                    if options.follow_symlinks {
+                        entry.path().is_dir()
+                    } else {
+                        entry.file_type().is_ok_and(|file_type| file_type.is_dir())
+                    }
+                })
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect();
+            names.sort();
+        }
+    }
+    let parent = directory.clone();
+    subdirectories.push((directory, relative_path.clone()));
+
+    for name in names {
+        let path = join_path(parent.as_deref(), &name);
+        if options.follow_symlinks
+            && !fs::canonicalize(&path).is_ok_and(|canonical_path| visited.insert(canonical_path))
+        {
+            continue;
+        }
+        let child_relative_path = if relative_path.is_empty() {
+            name
+        } else {
+            format!("{}/{}", relative_path, name)
+        };
+        collect_subdirectories(
+            Some(path),
+            child_relative_path,
+            depth + 1,
+            options,
+            visited,
+            subdirectories,
+        );
+    }
+}
+
+/// Returns the path of a file in the directory, which is the current one if it is [None].
+fn join_path(directory: Option<&str>, name: &str) -> String {
+    match directory {
+        Some(directory) => format!("{}/{}", directory, name),
+        None => name.to_string(),
+    }
+}
+
+/// Returns the path to read the directory by. An empty path is the root of an absolute path.
+fn readable_path(directory: Option<&str>) -> &str {
+    match directory {
+        None => ".",
+        Some("") => "/",
+        Some(directory) => directory,
+    }
+}
+
+fn generic_parts(pattern: &StringPattern, name: &str) -> Vec<String> {
+    pattern
+        .get_generic_parts(name)
+        .unwrap()
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Searches names of directory entries that match a given [StringPattern] and are accepted
+/// by the filter. Returns sorted names or [None] if nothing is found.
+fn search_names_by_pattern(
+    path_to_directory: &str,
+    pattern_file_name: &StringPattern,
+    is_wanted: impl Fn(&DirEntry) -> bool,
 ) -> Option<Vec<String>> {
     let mut matching_file_names = Vec::new();
     if let Ok(directory_entries) = fs::read_dir(path_to_directory) {
         for entry in directory_entries.flatten() {
-            if !entry.file_type().unwrap().is_file() {
+            if !is_wanted(&entry) {
                 continue;
             }
             let file_name = entry.file_name().to_string_lossy().to_string();
@@ -53,9 +283,13 @@ pub fn search_file_names_by_pattern(
 
 #[cfg(test)]
 mod tests {
-    use crate::{search_by_pattern::search_file_names_by_pattern, string_pattern::StringPattern};
+    use crate::path_pattern::PathPattern;
+    use crate::search_by_pattern::{
+        search_file_names_by_pattern, search_files_by_path_pattern, SearchOptions,
+    };
+    use crate::string_pattern::StringPattern;
 
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io::Write;
     use tempfile::{self, TempDir};
 
@@ -120,4 +354,168 @@ mod tests {
             relevant_file_names
         );
     }
+
+    #[test]
+    fn test_regex_metacharacters() {
+        let pattern: StringPattern = StringPattern::new("report.v1*(+).txt");
+        let relevant_file_names: Vec<String> = vec![
+            "report.v1(+).txt".to_string(),
+            "report.v1_final(+).txt".to_string(),
+        ];
+        let irrelevant_file_names = vec![
+            "reportXv1(+).txt".to_string(),
+            "report.v1().txt".to_string(),
+            "report.v1(+)_txt".to_string(),
+        ];
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for file_name in relevant_file_names.iter().chain(&irrelevant_file_names) {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+        assert_eq!(
+            search_file_names_by_pattern(directory_path, &pattern).unwrap(),
+            relevant_file_names
+        );
+    }
+
+    #[test]
+    fn test_wildcards_in_directories() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for directory in ["a_1/x", "a_2/x", "a_2/y", "b_3/x"] {
+            fs::create_dir_all(format!("{}/{}", directory_path, directory)).unwrap();
+        }
+        for file_name in ["a_1/x/1.log", "a_2/x/2.log", "a_2/y/3.log", "b_3/x/4.log"] {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+        File::create(format!("{}/a_file", directory_path)).unwrap();
+
+        let pattern = PathPattern::try_new(&format!("{}/a_*/x/*.log", directory_path)).unwrap();
+        let found_files =
+            search_files_by_path_pattern(&pattern, &SearchOptions::default()).unwrap();
+        assert_eq!(
+            found_files
+                .iter()
+                .map(|found_file| found_file.path.clone())
+                .collect::<Vec<String>>(),
+            vec![
+                format!("{}/a_1/x/1.log", directory_path),
+                format!("{}/a_2/x/2.log", directory_path),
+            ]
+        );
+        assert_eq!(found_files[1].captures, vec!["2", "2"]);
+
+        let pattern = PathPattern::try_new(&format!("{}/*/*/*.txt", directory_path)).unwrap();
+        assert_eq!(
+            search_files_by_path_pattern(&pattern, &SearchOptions::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_recursive_directories() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::create_dir_all(format!("{}/a/b/c", directory_path)).unwrap();
+        for file_name in ["0.rs", "a/1.rs", "a/b/2.rs", "a/b/c/3.rs"] {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+        let pattern = PathPattern::try_new(&format!("{}/**/*.rs", directory_path)).unwrap();
+        let captures = |options: &SearchOptions| -> Vec<Vec<String>> {
+            search_files_by_path_pattern(&pattern, options)
+                .unwrap()
+                .into_iter()
+                .map(|found_file| found_file.captures)
+                .collect()
+        };
+
+        assert_eq!(
+            captures(&SearchOptions::default()),
+            vec![
+                vec!["", "0"],
+                vec!["a", "1"],
+                vec!["a/b", "2"],
+                vec!["a/b/c", "3"]
+            ]
+        );
+        let options = SearchOptions {
+            max_depth: Some(1),
+            ..SearchOptions::default()
+        };
+        assert_eq!(captures(&options), vec![vec!["", "0"], vec!["a", "1"]]);
+    }
+
+    #[test]
+    fn test_several_recursive_directories() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::create_dir_all(format!("{}/a/b", directory_path)).unwrap();
+        for file_name in ["0.rs", "a/1.rs", "a/b/2.rs"] {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+        let pattern = PathPattern::try_new(&format!("{}/**/**/*.rs", directory_path)).unwrap();
+
+        let found_files =
+            search_files_by_path_pattern(&pattern, &SearchOptions::default()).unwrap();
+        assert_eq!(
+            found_files
+                .iter()
+                .map(|found_file| found_file.captures.clone())
+                .collect::<Vec<Vec<String>>>(),
+            vec![vec!["", "", "0"], vec!["", "a", "1"], vec!["", "a/b", "2"]]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_recursive_symlinks() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::create_dir_all(format!("{}/a", directory_path)).unwrap();
+        File::create(format!("{}/a/1.rs", directory_path)).unwrap();
+        std::os::unix::fs::symlink("..", format!("{}/a/loop", directory_path)).unwrap();
+        let pattern = PathPattern::try_new(&format!("{}/a/**/*.rs", directory_path)).unwrap();
+
+        let found_files = search_files_by_path_pattern(&pattern, &SearchOptions::default());
+        assert_eq!(found_files.unwrap().len(), 1);
+
+        let options = SearchOptions {
+            follow_symlinks: true,
+            ..SearchOptions::default()
+        };
+        let found_files = search_files_by_path_pattern(&pattern, &options).unwrap();
+        assert_eq!(
+            found_files
+                .iter()
+                .map(|found_file| found_file.captures.clone())
+                .collect::<Vec<Vec<String>>>(),
+            vec![vec!["", "1"]]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_files_behind_symlinks() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::create_dir_all(format!("{}/tree", directory_path)).unwrap();
+        fs::create_dir_all(format!("{}/elsewhere/b", directory_path)).unwrap();
+        File::create(format!("{}/elsewhere/b/2.rs", directory_path)).unwrap();
+        std::os::unix::fs::symlink("../elsewhere", format!("{}/tree/a", directory_path)).unwrap();
+        let pattern = PathPattern::try_new(&format!("{}/tree/**/*.rs", directory_path)).unwrap();
+
+        assert!(search_files_by_path_pattern(&pattern, &SearchOptions::default()).is_none());
+
+        let options = SearchOptions {
+            follow_symlinks: true,
+            ..SearchOptions::default()
+        };
+        let found_files = search_files_by_path_pattern(&pattern, &options).unwrap();
+        assert_eq!(found_files.len(), 1);
+        assert_eq!(
+            found_files[0].path,
+            format!("{}/tree/a/b/2.rs", directory_path)
+        );
+        assert_eq!(found_files[0].captures, vec!["a/b", "2"]);
+    }
 }
@@ -1,57 +1,326 @@
+use crate::error::MassMoveError;
 use regex::Regex;
 
+/// Characters that a backslash makes ordinary in source and target patterns.
+pub const ESCAPABLE_CHARACTERS: [char; 9] = ['\\', '*', '?', '[', ']', '{', '}', '#', '+'];
+
+/// A part of a [StringPattern].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternToken {
+    /// Text that is matched literally.
+    Literal(String),
+    /// '*': any substring, including an empty one. It is the shortest one unless the
+    /// star is greedy ('*+'), then it is the longest one.
+    Star { greedy: bool },
+    /// '{name}': any substring, including an empty one, that can be put into the target
+    /// pattern by its name as well as by its number.
+    Named(String),
+    /// '?': exactly one character.
+    AnyChar,
+    /// '[...]': exactly one character of the class. The set is the text between the brackets
+    /// without the leading '!' or '^' that makes the class negated.
+    CharClass { negated: bool, set: String },
+    /// '{jpg,png}': any of the comma-separated alternatives, which are matched literally.
+    Alternation(Vec<String>),
+    /// A regular expression of a pattern made by [StringPattern::from_regex]. Its groups are
+    /// the substrings of the pattern.
+    Regex(String),
+}
+
 /// An auxiliary structure that helps to work with a type of string patterns that look
-/// like a common string but '*' symbols means that any substring can be placed at that place.
+/// like a common string but '*' symbols means that any substring can be placed at that place
+/// and '?' symbols mean that any single character can be placed there.
+/// Bracket expressions like '[a-z]' or '[!0-9]' mean a single character of the class
+/// (or not of the class if it starts with '!' or '^'). They may contain ranges and POSIX
+/// classes like '[:digit:]', a ']' right after the opening bracket is a member of the class
+/// and a '[' without a closing bracket is an ordinary symbol.
+/// Braces with commas like '{jpg,jpeg,png}' mean any of the alternatives, the one that
+/// matched is a substring just like wildcards.
+/// Braces around a name without commas like '{date}' work like a '*' whose substring
+/// has that name. A name consists of ASCII letters, digits and '_' and doesn't start with
+/// a digit; every name may be used once in a pattern.
+/// A backslash before any of [ESCAPABLE_CHARACTERS] makes it an ordinary symbol, so '\*'
+/// matches a star and '\\' matches a backslash; other backslashes are ordinary themselves.
+/// All other symbols, including regex metacharacters like '.' or '(', are matched literally.
+/// Every wildcard is a capture group of the regex, so substrings hidden behind wildcards
+/// are numbered in order of wildcards.
+///
+/// Where a substring ends is decided from left to right: every '*' takes the shortest
+/// substring that lets the rest of the string match, so '*_*' splits 'a_b_c' into 'a' and
+/// 'b_c'. A greedy star '*+' takes the longest one instead, so '*+_*' splits it into 'a_b'
+/// and 'c'. Use '\+' for a '+' right after a star that is matched literally.
 pub struct StringPattern {
-    pub slices: Vec<String>,
+    pub tokens: Vec<PatternToken>,
     pub regex: Regex,
 }
 
 impl StringPattern {
     ///Returns new [StringPattern] formed by a string.
+    ///
+    /// # Panics
+    /// Panics if the pattern is invalid. Use [StringPattern::try_new] to handle it.
     pub fn new(raw_string_pattern: &str) -> Self {
-        let pattern_slices = Self::get_pattern_slices(raw_string_pattern);
-        let pattern_regex = Self::get_regex(&pattern_slices);
+        Self::try_new(raw_string_pattern).unwrap()
+    }
+
+    /// Returns new [StringPattern] formed by a string or
+    /// [MassMoveError::InvalidSourcePattern] if the pattern can't be compiled.
+    ///
+    /// # Examples
+    /// ```
+    /// use mass_move_lib::string_pattern::StringPattern;
+    ///
+    /// let pattern = StringPattern::try_new("report.v1*.txt").unwrap();
+    ///
+    /// assert!(pattern.is_matches("report.v1_final.txt"));
+    /// assert!(!pattern.is_matches("reportXv1_final.txt"));
+    /// ```
+    pub fn try_new(raw_string_pattern: &str) -> Result<Self, MassMoveError> {
+        let pattern_tokens = Self::get_pattern_tokens(raw_string_pattern);
+        let pattern_regex = Self::get_regex(&pattern_tokens)
+            .map_err(|_| MassMoveError::InvalidSourcePattern(raw_string_pattern.to_string()))?;
+        Ok(Self {
+            tokens: pattern_tokens,
+            regex: pattern_regex,
+        })
+    }
+
+    /// Returns [StringPattern] that matches strings by a regular expression instead of
+    /// wildcards, or [MassMoveError::InvalidSourcePattern] if the regex is invalid.
+    /// The regex has to match the whole string. Its capture groups, numbered and named,
+    /// are the substrings returned by [StringPattern::get_generic_parts]; groups that
+    /// didn't participate in the match are empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use mass_move_lib::string_pattern::StringPattern;
+    ///
+    /// let pattern = StringPattern::from_regex(r"(?<year>\d{4})-(\d{2})\.log").unwrap();
+    ///
+    /// assert_eq!(pattern.get_generic_parts("2024-05.log").unwrap(), vec!["2024", "05"]);
+    /// assert_eq!(pattern.get_generic_parts("2024-05.log.gz"), None);
+    /// assert_eq!(pattern.capture_names(), vec![Some("year".to_string()), None]);
+    /// ```
+    pub fn from_regex(raw_regex: &str) -> Result<Self, MassMoveError> {
+        let pattern_tokens = vec![PatternToken::Regex(raw_regex.to_string())];
+        let pattern_regex = Self::get_regex(&pattern_tokens)
+            .map_err(|_| MassMoveError::InvalidSourcePattern(raw_regex.to_string()))?;
+        Ok(Self {
+            tokens: pattern_tokens,
+            regex: pattern_regex,
+        })
+    }
+
+    /// Returns [StringPattern] for the same pattern that matches letters regardless of their
+    /// case. Substrings returned by [StringPattern::get_generic_parts] are still the original
+    /// text of the matched string.
+    ///
+    /// # Examples
+    /// ```
+    /// use mass_move_lib::string_pattern::StringPattern;
+    ///
+    /// let pattern = StringPattern::new("IMG_*.jpg").ignoring_case();
+    ///
+    /// assert!(pattern.is_matches("img_1.JPG"));
+    /// assert_eq!(pattern.get_generic_parts("Img_Beach.Jpg").unwrap(), vec!["Beach"]);
+    /// ```
+    pub fn ignoring_case(self) -> Self {
+        let regex = Regex::new(&format!("(?i){}", self.regex.as_str())).unwrap();
         Self {
-            slices: pattern_slices,
+            tokens: self.tokens,
+            regex,
+        }
+    }
+
+    /// Returns [StringPattern] that matches only the given text, with no wildcards.
+    pub fn from_literal(text: &str) -> Self {
+        let pattern_tokens = vec![PatternToken::Literal(text.to_string())];
+        let pattern_regex = Self::get_regex(&pattern_tokens).unwrap();
+        Self {
+            tokens: pattern_tokens,
             regex: pattern_regex,
         }
     }
 
-    /// Auxiliary function that is needed to create a [StringPattern] slices field when
-    /// making new object.
-    fn get_pattern_slices(raw_string_pattern: &str) -> Vec<String> {
-        let mut pattern_slices: Vec<String> = Vec::new();
-        let mut last_index_of_slices: usize = 0;
-        for letter in raw_string_pattern.chars() {
-            if letter == '*' {
-                pattern_slices.push(String::new());
-                if pattern_slices.len() == 1 {
-                    last_index_of_slices += 1;
-                } else {
-                    last_index_of_slices += 2;
+    /// Auxiliary function that is needed to create a [StringPattern] tokens field when
+    /// making new object. Neighbouring literal characters are joined into one token.
+    fn get_pattern_tokens(raw_string_pattern: &str) -> Vec<PatternToken> {
+        let letters: Vec<char> = raw_string_pattern.chars().collect();
+        let mut pattern_tokens: Vec<PatternToken> = Vec::new();
+        let mut index: usize = 0;
+        while index < letters.len() {
+            let letter = letters[index];
+            index += 1;
+            match letter {
+                '\\' if letters
+                    .get(index)
+                    .is_some_and(|next_letter| ESCAPABLE_CHARACTERS.contains(next_letter)) =>
+                {
+                    Self::push_literal(&mut pattern_tokens, letters[index]);
+                    index += 1;
                 }
+                '*' => {
+                    let greedy = letters.get(index) == Some(&'+');
+                    if greedy {
+                        index += 1;
+                    }
+                    pattern_tokens.push(PatternToken::Star { greedy });
+                }
+                '?' => pattern_tokens.push(PatternToken::AnyChar),
+                '[' => match Self::get_char_class(&letters[index..]) {
+                    Some((char_class, length)) => {
+                        pattern_tokens.push(char_class);
+                        index += length;
+                    }
+                    None => Self::push_literal(&mut pattern_tokens, letter),
+                },
+                '{' => match Self::get_alternation(&letters[index..])
+                    .or_else(|| Self::get_named_wildcard(&letters[index..]))
+                {
+                    Some((alternation, length)) => {
+                        pattern_tokens.push(alternation);
+                        index += length;
+                    }
+                    None => Self::push_literal(&mut pattern_tokens, letter),
+                },
+                _ => Self::push_literal(&mut pattern_tokens, letter),
+            }
+        }
+        pattern_tokens
+    }
+
+    fn push_literal(pattern_tokens: &mut Vec<PatternToken>, letter: char) {
+        match pattern_tokens.last_mut() {
+            Some(PatternToken::Literal(text)) => text.push(letter),
+            _ => pattern_tokens.push(PatternToken::Literal(letter.to_string())),
+        }
+    }
+
+    /// Parses a bracket expression that follows an opening bracket. Returns the class and
+    /// the number of letters it takes including the closing bracket, or [None] if the
+    /// bracket is never closed.
+    fn get_char_class(letters: &[char]) -> Option<(PatternToken, usize)> {
+        let negated = matches!(letters.first(), Some('!' | '^'));
+        let start = usize::from(negated);
+        let mut index = start;
+        if letters.get(index) == Some(&']') {
+            index += 1;
+        }
+        while index < letters.len() && letters[index] != ']' {
+            index += Self::get_posix_class_length(&letters[index..]).unwrap_or(1);
+        }
+        if index >= letters.len() {
+            return None;
+        }
+        let char_class = PatternToken::CharClass {
+            negated,
+            set: letters[start..index].iter().collect(),
+        };
+        Some((char_class, index + 1))
+    }
+
+    /// Parses alternatives that follow an opening brace. Returns them and the number of
+    /// letters they take including the closing brace, or [None] if the brace is never closed
+    /// or there is no comma before the closing brace.
+    fn get_alternation(letters: &[char]) -> Option<(PatternToken, usize)> {
+        let length = letters.iter().position(|&letter| letter == '}')?;
+        let content: String = letters[..length].iter().collect();
+        if !content.contains(',') {
+            return None;
+        }
+        let alternatives = content.split(',').map(str::to_string).collect();
+        Some((PatternToken::Alternation(alternatives), length + 1))
+    }
+
+    /// Parses a wildcard name that follows an opening brace. Returns the wildcard and the
+    /// number of letters it takes including the closing brace, or [None] if the brace is
+    /// never closed or the name is not a valid one.
+    fn get_named_wildcard(letters: &[char]) -> Option<(PatternToken, usize)> {
+        let length = letters.iter().position(|&letter| letter == '}')?;
+        let name = &letters[..length];
+        let is_valid = name.first().is_some_and(|letter| !letter.is_ascii_digit())
+            && name
+                .iter()
+                .all(|letter| letter.is_ascii_alphanumeric() || *letter == '_');
+        if !is_valid {
+            return None;
+        }
+        Some((PatternToken::Named(name.iter().collect()), length + 1))
+    }
+
+    /// Returns the length of a POSIX class like '[:digit:]' at the start of the letters.
+    /// Unknown class names are not classes.
+    fn get_posix_class_length(letters: &[char]) -> Option<usize> {
+        const POSIX_CLASSES: [&str; 12] = [
+            "alnum", "alpha", "blank", "cntrl", "digit", "graph", "lower", "print", "punct",
+            "space", "upper", "xdigit",
+        ];
+        if !letters.starts_with(&['[', ':']) {
+            return None;
+        }
+        let end =
+            (2..letters.len() - 1).find(|&end| letters[end] == ':' && letters[end + 1] == ']')?;
+        let name: String = letters[2..end].iter().collect();
+        POSIX_CLASSES.contains(&name.as_str()).then_some(end + 2)
+    }
+
+    /// Returns the regex class that matches the same characters as a bracket expression.
+    /// Everything but ranges and POSIX classes is escaped.
+    fn get_class_regex(negated: bool, set: &str) -> String {
+        let letters: Vec<char> = set.chars().collect();
+        let mut class_regex = String::from(if negated { "[^" } else { "[" });
+        let mut index: usize = 0;
+        while index < letters.len() {
+            if let Some(length) = Self::get_posix_class_length(&letters[index..]) {
+                class_regex.extend(&letters[index..index + length]);
+                index += length;
                 continue;
             }
-            if last_index_of_slices as i32 > (pattern_slices.len() as i32 - 1) {
-                pattern_slices.push("".to_string());
+            let letter = letters[index];
+            if letter == '-' && index > 0 && index + 1 < letters.len() {
+                class_regex.push('-');
+            } else {
+                class_regex.push_str(&regex::escape(&letter.to_string()));
             }
-            pattern_slices[last_index_of_slices] += &letter.to_string();
+            index += 1;
         }
-        pattern_slices
+        class_regex.push(']');
+        class_regex
     }
+
     /// Auxiliary function that is needed to create a [StringPattern] regex field when
-    /// making new object
-    fn get_regex(pattern_slices: &[String]) -> Regex {
+    /// making new object. Literal tokens are escaped so they match only themselves,
+    /// every wildcard becomes a capture group.
+    fn get_regex(pattern_tokens: &[PatternToken]) -> Result<Regex, regex::Error> {
         let mut regex_pattern_string = String::new();
-        for pattern_slice in pattern_slices.iter() {
-            regex_pattern_string.push_str(pattern_slice);
-            if pattern_slice.is_empty() {
-                regex_pattern_string.push_str(r".*?");
+        for pattern_token in pattern_tokens {
+            match pattern_token {
+                PatternToken::Literal(text) => regex_pattern_string.push_str(&regex::escape(text)),
+                PatternToken::Star { greedy: false } => regex_pattern_string.push_str("(.*?)"),
+                PatternToken::Star { greedy: true } => regex_pattern_string.push_str("(.*)"),
+                PatternToken::Named(name) => {
+                    regex_pattern_string.push_str(&format!("(?<{}>.*?)", name));
+                }
+                PatternToken::AnyChar => regex_pattern_string.push_str("(.)"),
+                PatternToken::Alternation(alternatives) => {
+                    let escaped: Vec<String> = alternatives
+                        .iter()
+                        .map(|alternative| regex::escape(alternative))
+                        .collect();
+                    regex_pattern_string.push_str(&format!("({})", escaped.join("|")));
+                }
+                PatternToken::Regex(text) => {
+                    regex_pattern_string.push_str(&format!("(?:{})", text));
+                }
+                PatternToken::CharClass { negated, set } => {
+                    regex_pattern_string.push('(');
+                    regex_pattern_string.push_str(&Self::get_class_regex(*negated, set));
+                    regex_pattern_string.push(')');
+                }
             }
         }
-        regex_pattern_string = format!("^{}$", regex_pattern_string);
-        Regex::new(&regex_pattern_string).unwrap()
+        regex_pattern_string = format!("(?s)^{}$", regex_pattern_string);
+        Regex::new(&regex_pattern_string)
     }
 
     /// Returns true if and only if a given string matches the pattern.
@@ -72,9 +341,51 @@ impl StringPattern {
         self.regex.is_match(string_to_check)
     }
 
+    /// Returns the only string that matches the pattern or [None] if the pattern has
+    /// wildcards.
+    ///
+    /// # Examples
+    /// ```
+    /// use mass_move_lib::string_pattern::StringPattern;
+    ///
+    /// assert_eq!(StringPattern::new("a.txt").literal(), Some("a.txt".to_string()));
+    /// assert_eq!(StringPattern::new("*.txt").literal(), None);
+    /// ```
+    pub fn literal(&self) -> Option<String> {
+        let mut literal = String::new();
+        for pattern_token in &self.tokens {
+            let PatternToken::Literal(text) = pattern_token else {
+                return None;
+            };
+            literal.push_str(text);
+        }
+        Some(literal)
+    }
+
+    /// Returns the names of the substrings returned by [StringPattern::get_generic_parts],
+    /// [None] for substrings without a name.
+    ///
+    /// # Examples
+    /// ```
+    /// use mass_move_lib::string_pattern::StringPattern;
+    ///
+    /// let pattern = StringPattern::new("{date}_*.jpg");
+    ///
+    /// assert_eq!(pattern.capture_names(), vec![Some("date".to_string()), None]);
+    /// assert_eq!(pattern.get_generic_parts("0105_a.jpg").unwrap(), vec!["0105", "a"]);
+    /// ```
+    pub fn capture_names(&self) -> Vec<Option<String>> {
+        self.regex
+            .capture_names()
+            .skip(1)
+            .map(|name| name.map(str::to_string))
+            .collect()
+    }
+
     /// Returns [Option<Vec<&str>>] which is [None] if a given string doesn't match the pattern.
     /// If the string matches function will return a vector of substrings that hide
-    /// behind '*' symbols of string pattern.
+    /// behind wildcards of string pattern. They are the capture groups of the regex, so
+    /// they are split exactly as the string was matched (check [StringPattern]).
     ///
     /// # Examples
     /// ```
@@ -88,34 +399,32 @@ impl StringPattern {
     /// assert_eq!(pattern.get_generic_parts("ab123cde321").unwrap(), vec!["123", "321"]);
     /// assert_eq!(pattern.get_generic_parts("ab123cde").unwrap(), vec!["123", ""]);
     /// assert_eq!(pattern.get_generic_parts("abababcdecdecdef").unwrap(), vec!["abab", "cdecdef"]);
+    ///
+    /// let pattern: StringPattern = StringPattern::new("IMG_????.jpg");
+    ///
+    /// assert_eq!(pattern.get_generic_parts("IMG_2024.jpg").unwrap(), vec!["2", "0", "2", "4"]);
+    /// assert_eq!(pattern.get_generic_parts("IMG_12345.jpg"), None);
+    ///
+    /// let lazy: StringPattern = StringPattern::new("*_*");
+    /// let greedy: StringPattern = StringPattern::new("*+_*");
+    ///
+    /// assert_eq!(lazy.get_generic_parts("a_b_c").unwrap(), vec!["a", "b_c"]);
+    /// assert_eq!(greedy.get_generic_parts("a_b_c").unwrap(), vec!["a_b", "c"]);
     /// ```
     pub fn get_generic_parts<'a>(&self, string_to_decompose: &'a str) -> Option<Vec<&'a str>> {
-        if !self.is_matches(string_to_decompose) {
-            return None;
-        }
-        let mut generic_parts: Vec<&'a str> = Vec::new();
-        let mut residual_string = string_to_decompose;
-        for pattern_slice in self.slices.iter() {
-            if pattern_slice.is_empty() {
-                generic_parts.push("");
-                continue;
-            }
-            let splitted_residual_string = residual_string.split_once(pattern_slice).unwrap();
-            if !splitted_residual_string.0.is_empty() {
-                generic_parts.pop();
-                generic_parts.push(splitted_residual_string.0);
-            }
-            residual_string = splitted_residual_string.1
-        }
-        if !residual_string.is_empty() {
-            generic_parts.pop();
-            generic_parts.push(residual_string);
-        }
-        Some(generic_parts)
+        let captures = self.regex.captures(string_to_decompose)?;
+        Some(
+            captures
+                .iter()
+                .skip(1)
+                .map(|capture| capture.map_or("", |capture| capture.as_str()))
+                .collect(),
+        )
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     #[cfg(test)]
     mod test_is_matches {
@@ -231,4 +540,358 @@ mod tests {
             assert_eq!(pattern.get_generic_parts("1abc").unwrap(), vec!["1"]);
         }
     }
+    #[cfg(test)]
+    mod test_any_char {
+        use crate::string_pattern::StringPattern;
+
+        #[test]
+        fn test_exactly_one_character() {
+            let pattern = StringPattern::new("IMG_????.jpg");
+            assert_eq!(pattern.is_matches("IMG_0001.jpg"), true);
+            assert_eq!(pattern.is_matches("IMG_001.jpg"), false);
+            assert_eq!(pattern.is_matches("IMG_00001.jpg"), false);
+            assert_eq!(pattern.is_matches("IMG_.jpg"), false);
+        }
+
+        #[test]
+        fn test_unicode() {
+            let pattern = StringPattern::new("?_?.txt");
+            assert_eq!(
+                pattern.get_generic_parts("é_\n.txt").unwrap(),
+                vec!["é", "\n"]
+            );
+            assert_eq!(pattern.get_generic_parts("ab_c.txt"), None);
+        }
+
+        #[test]
+        fn test_with_stars() {
+            let pattern = StringPattern::new("*?");
+            assert_eq!(pattern.get_generic_parts("abc").unwrap(), vec!["ab", "c"]);
+            assert_eq!(pattern.get_generic_parts(""), None);
+
+            let pattern = StringPattern::new("a**?b");
+            assert_eq!(
+                pattern.get_generic_parts("a12b").unwrap(),
+                vec!["", "1", "2"]
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod test_char_class {
+        use crate::string_pattern::StringPattern;
+
+        #[test]
+        fn test_ranges() {
+            let pattern = StringPattern::new("chapter[0-9]*.md");
+            assert_eq!(pattern.is_matches("chapter1.md"), true);
+            assert_eq!(pattern.is_matches("chapter12_intro.md"), true);
+            assert_eq!(pattern.is_matches("chapter_1.md"), false);
+            assert_eq!(
+                pattern.get_generic_parts("chapter12.md").unwrap(),
+                vec!["1", "2"]
+            );
+
+            let pattern = StringPattern::new("[a-cx]");
+            assert_eq!(pattern.is_matches("b"), true);
+            assert_eq!(pattern.is_matches("x"), true);
+            assert_eq!(pattern.is_matches("d"), false);
+            assert_eq!(pattern.is_matches("-"), false);
+        }
+
+        #[test]
+        fn test_negation() {
+            for raw_pattern in ["[!0-9].txt", "[^0-9].txt"] {
+                let pattern = StringPattern::new(raw_pattern);
+                assert_eq!(pattern.is_matches("a.txt"), true, "{}", raw_pattern);
+                assert_eq!(pattern.is_matches("1.txt"), false, "{}", raw_pattern);
+                assert_eq!(pattern.is_matches("!.txt"), true, "{}", raw_pattern);
+            }
+        }
+
+        #[test]
+        fn test_special_members() {
+            let pattern = StringPattern::new("[]-]");
+            assert_eq!(pattern.is_matches("]"), true);
+            assert_eq!(pattern.is_matches("-"), true);
+            assert_eq!(pattern.is_matches("a"), false);
+
+            let pattern = StringPattern::new("[*?.\\&&~~]");
+            for member in ["*", "?", ".", "\\", "&", "~"] {
+                assert_eq!(pattern.is_matches(member), true, "{}", member);
+            }
+            assert_eq!(pattern.is_matches("a"), false);
+
+            let pattern = StringPattern::new("[[:digit:][:upper:]_]");
+            assert_eq!(pattern.get_generic_parts("7").unwrap(), vec!["7"]);
+            assert_eq!(pattern.is_matches("Q"), true);
+            assert_eq!(pattern.is_matches("_"), true);
+            assert_eq!(pattern.is_matches("q"), false);
+        }
+
+        #[test]
+        fn test_unclosed_bracket_is_literal() {
+            let pattern = StringPattern::new("a[0-9*");
+            assert_eq!(pattern.get_generic_parts("a[0-9xyz").unwrap(), vec!["xyz"]);
+            assert_eq!(pattern.is_matches("a1"), false);
+
+            let pattern = StringPattern::new("[]");
+            assert_eq!(pattern.is_matches("[]"), true);
+        }
+
+        #[test]
+        fn test_invalid_and_unknown_classes() {
+            assert!(StringPattern::try_new("[z-a]").is_err());
+
+            let pattern = StringPattern::new("[[:unknown:]]");
+            assert_eq!(pattern.is_matches("u]"), true);
+            assert_eq!(pattern.is_matches("1]"), false);
+        }
+    }
+
+    #[cfg(test)]
+    mod test_alternation {
+        use crate::string_pattern::StringPattern;
+
+        #[test]
+        fn test_extensions() {
+            let pattern = StringPattern::new("photo_*.{jpg,JPG,jpeg}");
+            assert_eq!(
+                pattern.get_generic_parts("photo_1.jpeg").unwrap(),
+                vec!["1", "jpeg"]
+            );
+            assert_eq!(
+                pattern.get_generic_parts("photo_2.JPG").unwrap(),
+                vec!["2", "JPG"]
+            );
+            assert_eq!(pattern.is_matches("photo_3.png"), false);
+            assert_eq!(pattern.is_matches("photo_3.jp"), false);
+        }
+
+        #[test]
+        fn test_empty_and_special_alternatives() {
+            let pattern = StringPattern::new("file{,s}.{a.b,(c)}");
+            assert_eq!(
+                pattern.get_generic_parts("file.(c)").unwrap(),
+                vec!["", "(c)"]
+            );
+            assert_eq!(
+                pattern.get_generic_parts("files.a.b").unwrap(),
+                vec!["s", "a.b"]
+            );
+            assert_eq!(pattern.is_matches("files.aXb"), false);
+        }
+
+        #[test]
+        fn test_braces_without_commas_are_literal() {
+            let pattern = StringPattern::new("{1x}_{}_{a-b}_{a,b");
+            assert_eq!(pattern.literal(), Some("{1x}_{}_{a-b}_{a,b".to_string()));
+        }
+    }
+
+    #[cfg(test)]
+    mod test_named {
+        use crate::string_pattern::StringPattern;
+
+        #[test]
+        fn test_names() {
+            let pattern = StringPattern::new("{date}_{cam_2}_*.jpg");
+            assert_eq!(
+                pattern.get_generic_parts("0105_nikon_1.jpg").unwrap(),
+                vec!["0105", "nikon", "1"]
+            );
+            assert_eq!(
+                pattern.capture_names(),
+                vec![Some("date".to_string()), Some("cam_2".to_string()), None]
+            );
+            assert_eq!(pattern.is_matches("0105.jpg"), false);
+        }
+
+        #[test]
+        fn test_with_alternation_and_escapes() {
+            let pattern = StringPattern::new(r"{name}.{jpg,png}\{name\}");
+            assert_eq!(
+                pattern.get_generic_parts("a.png{name}").unwrap(),
+                vec!["a", "png"]
+            );
+        }
+
+        #[test]
+        fn test_repeated_name() {
+            assert!(StringPattern::try_new("{a}_{a}").is_err());
+        }
+    }
+
+    #[cfg(test)]
+    mod test_greedy_star {
+        use crate::string_pattern::{PatternToken, StringPattern};
+
+        #[test]
+        fn test_split() {
+            let pattern = StringPattern::new("*+.*");
+            assert_eq!(
+                pattern.get_generic_parts("archive.tar.gz").unwrap(),
+                vec!["archive.tar", "gz"]
+            );
+            let pattern = StringPattern::new("*.*+");
+            assert_eq!(
+                pattern.get_generic_parts("archive.tar.gz").unwrap(),
+                vec!["archive", "tar.gz"]
+            );
+            let pattern = StringPattern::new("*+_*+");
+            assert_eq!(
+                pattern.get_generic_parts("a_b_c").unwrap(),
+                vec!["a_b", "c"]
+            );
+        }
+
+        #[test]
+        fn test_matches_like_lazy_star() {
+            for string_to_check in ["", "a_", "_b", "a_b_c", "abc"] {
+                assert_eq!(
+                    StringPattern::new("*+_*").is_matches(string_to_check),
+                    StringPattern::new("*_*").is_matches(string_to_check),
+                    "{}",
+                    string_to_check
+                );
+            }
+        }
+
+        #[test]
+        fn test_plus_elsewhere() {
+            let pattern = StringPattern::new(r"c+*\+*");
+            assert_eq!(pattern.get_generic_parts("c+a+b").unwrap(), vec!["a", "b"]);
+            assert_eq!(
+                pattern
+                    .tokens
+                    .iter()
+                    .filter(|token| matches!(token, PatternToken::Star { greedy: false }))
+                    .count(),
+                2
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod test_ignoring_case {
+        use crate::string_pattern::StringPattern;
+
+        #[test]
+        fn test_literal_parts() {
+            let pattern = StringPattern::new("photo_*.{jpg,png}").ignoring_case();
+            assert_eq!(pattern.is_matches("PHOTO_1.JPG"), true);
+            assert_eq!(pattern.is_matches("Photo_1.Png"), true);
+            assert_eq!(pattern.is_matches("photo_1.gif"), false);
+            assert_eq!(
+                pattern.get_generic_parts("PhOtO_Sea.JpG").unwrap(),
+                vec!["Sea", "JpG"]
+            );
+        }
+
+        #[test]
+        fn test_classes_and_regex() {
+            let pattern = StringPattern::new("[a-c]*").ignoring_case();
+            assert_eq!(pattern.get_generic_parts("Bx").unwrap(), vec!["B", "x"]);
+
+            let pattern = StringPattern::from_regex(r"(\w+)\.log")
+                .unwrap()
+                .ignoring_case();
+            assert_eq!(pattern.get_generic_parts("App.LOG").unwrap(), vec!["App"]);
+        }
+
+        #[test]
+        fn test_case_sensitive_by_default() {
+            assert_eq!(StringPattern::new("*.jpg").is_matches("a.JPG"), false);
+            assert_eq!(
+                StringPattern::from_literal("a*.JPG").is_matches("a*.JPG"),
+                true
+            );
+            assert_eq!(
+                StringPattern::from_literal("a*.JPG").is_matches("ab.JPG"),
+                false
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod test_escapes {
+        use crate::string_pattern::StringPattern;
+
+        #[test]
+        fn test_escaped_wildcards() {
+            let pattern = StringPattern::new(r"\*_*_\?_\[a]_\{a,b}");
+            assert_eq!(
+                pattern.get_generic_parts("*_x_?_[a]_{a,b}").unwrap(),
+                vec!["x"]
+            );
+            assert_eq!(pattern.is_matches("a_x_b_a_a"), false);
+            assert_eq!(
+                StringPattern::new(r"\*\*").literal(),
+                Some("**".to_string())
+            );
+        }
+
+        #[test]
+        fn test_backslashes() {
+            let pattern = StringPattern::new(r"a\\*");
+            assert_eq!(pattern.get_generic_parts(r"a\b").unwrap(), vec!["b"]);
+            assert_eq!(pattern.is_matches("ab"), false);
+
+            let pattern = StringPattern::new(r"a\b\#1\");
+            assert_eq!(pattern.literal(), Some(r"a\b#1\".to_string()));
+        }
+    }
+
+    #[cfg(test)]
+    mod test_metacharacters {
+        use crate::string_pattern::StringPattern;
+
+        const METACHARACTERS: [&str; 11] = [".", "(", ")", "[", "]", "{", "}", "^", "$", "|", "-"];
+
+        #[test]
+        fn test_matched_literally() {
+            for metacharacter in METACHARACTERS {
+                let name = format!("a{}b", metacharacter);
+                let pattern = StringPattern::try_new(&name).unwrap();
+                assert!(pattern.is_matches(&name), "{}", name);
+                assert!(!pattern.is_matches("axb"), "{}", name);
+                assert!(!pattern.is_matches("ab"), "{}", name);
+            }
+        }
+
+        #[test]
+        fn test_with_stars() {
+            for metacharacter in METACHARACTERS {
+                let pattern_string = format!("*{}*", metacharacter);
+                let pattern = StringPattern::try_new(&pattern_string).unwrap();
+                let name = format!("a{}b", metacharacter);
+                assert_eq!(
+                    pattern.get_generic_parts(&name).unwrap(),
+                    vec!["a", "b"],
+                    "{}",
+                    pattern_string
+                );
+                assert_eq!(pattern.get_generic_parts("axb"), None, "{}", pattern_string);
+            }
+        }
+
+        #[test]
+        fn test_all_together() {
+            let pattern = StringPattern::try_new("report(v1)+]*[{1}^$|.txt").unwrap();
+            assert!(pattern.is_matches("report(v1)+]draft[{1}^$|.txt"));
+            assert!(!pattern.is_matches("report(v1)+]draft[{1}^$|_txt"));
+            assert!(!pattern.is_matches("reportv1]draft[{1}.txt"));
+            assert_eq!(
+                pattern
+                    .get_generic_parts("report(v1)+]draft[{1}^$|.txt")
+                    .unwrap(),
+                vec!["draft"]
+            );
+
+            let pattern = StringPattern::try_new("report.v1*.txt").unwrap();
+            assert!(pattern.is_matches("report.v1.txt"));
+            assert!(!pattern.is_matches("report_v1.txt"));
+            assert!(!pattern.is_matches("report.v1_txt"));
+        }
+    }
 }
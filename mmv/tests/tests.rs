@@ -1,5 +1,23 @@
+/// Returns sorted names and contents of all files in the directory.
+#[cfg(test)]
+fn read_directory(directory_path: &str) -> Vec<(String, String)> {
+    let mut files: Vec<(String, String)> = std::fs::read_dir(directory_path)
+        .unwrap()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            (
+                entry.file_name().to_string_lossy().to_string(),
+                std::fs::read_to_string(entry.path()).unwrap(),
+            )
+        })
+        .collect();
+    files.sort();
+    files
+}
+
 #[cfg(test)]
 mod test_mass_move {
+    use mass_move_lib::error::MassMoveError;
     use mass_move_lib::mass_move::mass_move;
     use std::fs;
     use std::fs::File;
@@ -8,10 +26,59 @@ mod test_mass_move {
 
     #[test]
     fn test_empty() {
-        assert_eq!(
+        assert!(matches!(
             mass_move("", "", false),
-            Err("Incorrect source_pattern".to_string())
-        );
+            Err(MassMoveError::InvalidSourcePattern(pattern)) if pattern.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_errors() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        assert!(matches!(
+            mass_move(&(directory_path.to_string() + "/*"), "#1", false),
+            Err(MassMoveError::InvalidTargetPattern(_))
+        ));
+        assert!(matches!(
+            mass_move(
+                &(directory_path.to_string() + "/*"),
+                &(directory_path.to_string() + "/#1"),
+                false
+            ),
+            Err(MassMoveError::NoMatches(_))
+        ));
+
+        File::create(format!("{}/a.txt", directory_path)).unwrap();
+        File::create(format!("{}/b.txt", directory_path)).unwrap();
+        assert!(matches!(
+            mass_move(
+                &(directory_path.to_string() + "/a.*"),
+                &(directory_path.to_string() + "/#2.#1"),
+                false
+            ),
+            Err(MassMoveError::PlaceholderOutOfRange {
+                index: 2,
+                available: 1
+            })
+        ));
+        assert!(matches!(
+            mass_move(
+                &(directory_path.to_string() + "/a.*"),
+                &(directory_path.to_string() + "/b.#1"),
+                false
+            ),
+            Err(MassMoveError::DestinationExists(_))
+        ));
+
+        let error = mass_move(
+            &(directory_path.to_string() + "/a.*"),
+            &(directory_path.to_string() + "/not_exists/a.#1"),
+            false,
+        )
+        .unwrap_err();
+        assert!(matches!(error, MassMoveError::Io { .. }));
+        assert!(std::error::Error::source(&error).is_some());
     }
 
     #[test]
@@ -83,7 +150,7 @@ mod test_mass_move {
             file.write_all(b"hihihihi").unwrap();
         }
 
-        let res: Result<(), String> = mass_move(
+        let res: Result<(), MassMoveError> = mass_move(
             &(directory_path1.to_string() + "/some_*_filename.*"),
             &(directory_path2.to_string() + "/changed_#1_filename.#2"),
             false,
@@ -102,3 +169,1336 @@ mod test_mass_move {
         assert_eq!(new_file_names, moved_files);
     }
 }
+
+#[cfg(test)]
+mod test_plan {
+    use super::read_directory;
+    use mass_move_lib::error::MassMoveError;
+    use mass_move_lib::execute::{execute, ExecuteOptions};
+    use mass_move_lib::rename_plan::{
+        plan, plan_with_options, Conflict, Operation, PlanOptions, RenamePlan,
+    };
+    use std::fs;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::{self, TempDir};
+
+    #[test]
+    fn test_nothing_is_moved() {
+        let file_names: Vec<String> = vec!["a_1.txt".to_string(), "a_2.txt".to_string()];
+
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for file_name in &file_names {
+            let file_path = format!("{}/{}", directory_path, file_name);
+            let mut file = File::create(&file_path).unwrap();
+            file.write_all(b"hihihihi").unwrap();
+        }
+        File::create(format!("{}/b_2.txt", directory_path)).unwrap();
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/a_*.txt"),
+            &(directory_path.to_string() + "/b_#1.txt"),
+        )
+        .unwrap();
+
+        let moves: Vec<(String, String)> = rename_plan
+            .entries
+            .iter()
+            .map(|entry| (entry.source.clone(), entry.destination.clone()))
+            .collect();
+        assert_eq!(
+            moves,
+            vec![
+                (
+                    format!("{}/a_1.txt", directory_path),
+                    format!("{}/b_1.txt", directory_path)
+                ),
+                (
+                    format!("{}/a_2.txt", directory_path),
+                    format!("{}/b_2.txt", directory_path)
+                ),
+            ]
+        );
+        assert_eq!(
+            rename_plan.conflicts(),
+            vec![Conflict::DestinationExists(format!(
+                "{}/b_2.txt",
+                directory_path
+            ))]
+        );
+
+        let mut files: Vec<String> = fs::read_dir(directory_path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["a_1.txt", "a_2.txt", "b_2.txt"]);
+    }
+
+    #[test]
+    fn test_not_found() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        assert!(plan(
+            &(directory_path.to_string() + "/*.txt"),
+            &(directory_path.to_string() + "/#1.bin"),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_wildcards_in_source_directories() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for project in ["app", "lib"] {
+            fs::create_dir_all(format!("{}/projects/{}/build", directory_path, project)).unwrap();
+            fs::write(
+                format!("{}/projects/{}/build/debug.log", directory_path, project),
+                project,
+            )
+            .unwrap();
+        }
+        fs::create_dir(format!("{}/logs", directory_path)).unwrap();
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/projects/*/build/*.log"),
+            &(directory_path.to_string() + "/logs/#1_#2.log"),
+        )
+        .unwrap();
+        assert_eq!(rename_plan.entries[1].captures, vec!["lib", "debug"]);
+        assert!(execute(&rename_plan, &ExecuteOptions::default()).is_ok());
+        assert_eq!(
+            read_directory(&format!("{}/logs", directory_path)),
+            vec![
+                ("app_debug.log".to_string(), "app".to_string()),
+                ("lib_debug.log".to_string(), "lib".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alternatives_in_source_pattern() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for file_name in ["photo_1.jpg", "photo_2.JPG", "photo_3.jpeg", "photo_4.png"] {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/photo_*.{jpg,JPG,jpeg}"),
+            &(directory_path.to_string() + "/#2_#1.#2"),
+        )
+        .unwrap();
+        assert_eq!(
+            rename_plan
+                .entries
+                .iter()
+                .map(|entry| entry.destination.clone())
+                .collect::<Vec<String>>(),
+            vec![
+                format!("{}/jpg_1.jpg", directory_path),
+                format!("{}/JPG_2.JPG", directory_path),
+                format!("{}/jpeg_3.jpeg", directory_path),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_escapes() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for file_name in ["*_1.txt", "x_2.txt"] {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + r"/\*_*.txt"),
+            &(directory_path.to_string() + r"/\#1_#1\\.txt"),
+        )
+        .unwrap();
+        assert_eq!(rename_plan.entries.len(), 1);
+        assert_eq!(
+            rename_plan.entries[0].destination,
+            format!(r"{}/#1_1\.txt", directory_path)
+        );
+    }
+
+    #[test]
+    fn test_named_wildcards() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for file_name in ["0105_nikon_1.jpg", "0106_canon_2.jpg"] {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/{date}_{cam}_*.jpg"),
+            &(directory_path.to_string() + "/#{cam}_#{date}_#3.jpg"),
+        )
+        .unwrap();
+        let destinations: Vec<String> = rename_plan
+            .entries
+            .iter()
+            .map(|entry| entry.destination.clone())
+            .collect();
+        assert_eq!(
+            destinations,
+            vec![
+                format!("{}/nikon_0105_1.jpg", directory_path),
+                format!("{}/canon_0106_2.jpg", directory_path)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignore_case() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::create_dir(format!("{}/Camera", directory_path)).unwrap();
+        for file_name in ["a.jpg", "B.JPG", "c.Jpg", "d.png"] {
+            File::create(format!("{}/Camera/{}", directory_path, file_name)).unwrap();
+        }
+
+        let options = PlanOptions {
+            ignore_case: true,
+            ..PlanOptions::default()
+        };
+        let rename_plan = plan_with_options(
+            &(directory_path.to_string() + "/camera/*.jpg"),
+            &(directory_path.to_string() + "/#1.jpg"),
+            &options,
+        )
+        .unwrap();
+        execute(&rename_plan, &ExecuteOptions::default()).unwrap();
+        for file_name in ["a.jpg", "B.jpg", "c.jpg", "Camera/d.png"] {
+            assert!(fs::exists(format!("{}/{}", directory_path, file_name)).unwrap());
+        }
+        assert_eq!(
+            read_directory(&format!("{}/Camera", directory_path)).len(),
+            1
+        );
+
+        assert!(matches!(
+            plan(
+                &(directory_path.to_string() + "/Camera/*.JPG"),
+                &(directory_path.to_string() + "/#1.jpg"),
+            ),
+            Err(MassMoveError::NoMatches(_))
+        ));
+    }
+
+    #[test]
+    fn test_case_modifiers() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for file_name in ["Beach Trip.JPG", "myFamily.JPG"] {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/{name}.*"),
+            &(directory_path.to_string() + "/#{name}:snake.#2:lower"),
+        )
+        .unwrap();
+        execute(&rename_plan, &ExecuteOptions::default()).unwrap();
+        for file_name in ["beach_trip.jpg", "my_family.jpg"] {
+            assert!(fs::exists(format!("{}/{}", directory_path, file_name)).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_regex() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for file_name in ["20240105_app.log", "20240106_db.log", "notes_app.log"] {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+
+        let options = PlanOptions {
+            regex: true,
+            ..PlanOptions::default()
+        };
+        let rename_plan = plan_with_options(
+            &(directory_path.to_string() + r"/(?<day>\d{8})_(\w+)\.log"),
+            &(directory_path.to_string() + "/#2/#{day}.log"),
+            &options,
+        )
+        .unwrap();
+        execute(&rename_plan, &ExecuteOptions::default()).unwrap_err();
+
+        let options = ExecuteOptions {
+            create_directories: true,
+            ..ExecuteOptions::default()
+        };
+        execute(&rename_plan, &options).unwrap();
+        assert!(fs::exists(format!("{}/app/20240105.log", directory_path)).unwrap());
+        assert!(fs::exists(format!("{}/db/20240106.log", directory_path)).unwrap());
+        assert!(fs::exists(format!("{}/notes_app.log", directory_path)).unwrap());
+
+        assert!(matches!(
+            plan_with_options(
+                &(directory_path.to_string() + r"/(\w+)\.log"),
+                &(directory_path.to_string() + "/#{name}.log"),
+                &PlanOptions {
+                    regex: true,
+                    ..PlanOptions::default()
+                },
+            ),
+            Err(MassMoveError::UnknownPlaceholder(_))
+        ));
+    }
+
+    #[test]
+    fn test_saved_plan() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for file_name in ["a_1.txt", "a_2.txt"] {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/a_*.txt"),
+            &(directory_path.to_string() + "/b/#1.txt"),
+        )
+        .unwrap();
+        rename_plan.operation = Operation::Copy;
+        let saved = serde_json::to_string(&rename_plan).unwrap();
+        let loaded_plan: RenamePlan = serde_json::from_str(&saved).unwrap();
+        assert_eq!(loaded_plan, rename_plan);
+
+        let options = ExecuteOptions {
+            create_directories: true,
+            ..ExecuteOptions::default()
+        };
+        execute(&loaded_plan, &options).unwrap();
+        assert_eq!(read_directory(&format!("{}/b", directory_path)).len(), 2);
+        assert!(fs::exists(format!("{}/a_1.txt", directory_path)).unwrap());
+    }
+
+    #[test]
+    fn test_execute_filtered_plan() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for file_name in ["a_1.txt", "a_2.txt", "a_3.txt"] {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/a_*.txt"),
+            &(directory_path.to_string() + "/b_#1.txt"),
+        )
+        .unwrap();
+        assert_eq!(rename_plan.entries[1].captures, vec!["2".to_string()]);
+        rename_plan.entries.remove(1);
+        assert!(execute(&rename_plan, &ExecuteOptions::default()).is_ok());
+
+        let mut files: Vec<String> = fs::read_dir(directory_path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["a_2.txt", "b_1.txt", "b_3.txt"]);
+    }
+
+    #[test]
+    fn test_execute_refuses_conflicts() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for file_name in ["a_1.txt", "a_2.txt", "b_2.txt"] {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/a_*.txt"),
+            &(directory_path.to_string() + "/b_#1.txt"),
+        )
+        .unwrap();
+        assert!(execute(&rename_plan, &ExecuteOptions::default()).is_err());
+        assert!(fs::metadata(format!("{}/a_1.txt", directory_path)).is_ok());
+
+        assert!(execute(
+            &rename_plan,
+            &ExecuteOptions {
+                force: true,
+                ..ExecuteOptions::default()
+            }
+        )
+        .is_ok());
+        assert!(fs::metadata(format!("{}/b_1.txt", directory_path)).is_ok());
+        assert!(fs::metadata(format!("{}/a_2.txt", directory_path)).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_destinations() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for file_name in [
+            "a_1_x.txt",
+            "a_1_y.txt",
+            "a_2_x.txt",
+            "a_3_x.txt",
+            "a_3_z.txt",
+        ] {
+            File::create(format!("{}/{}", directory_path, file_name)).unwrap();
+        }
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/a_*_*.txt"),
+            &(directory_path.to_string() + "/#1.txt"),
+        )
+        .unwrap();
+        assert_eq!(
+            rename_plan.conflicts(),
+            vec![
+                Conflict::DuplicateDestination {
+                    destination: format!("{}/1.txt", directory_path),
+                    sources: vec![
+                        format!("{}/a_1_x.txt", directory_path),
+                        format!("{}/a_1_y.txt", directory_path),
+                    ],
+                },
+                Conflict::DuplicateDestination {
+                    destination: format!("{}/3.txt", directory_path),
+                    sources: vec![
+                        format!("{}/a_3_x.txt", directory_path),
+                        format!("{}/a_3_z.txt", directory_path),
+                    ],
+                },
+            ]
+        );
+
+        assert!(matches!(
+            execute(
+                &rename_plan,
+                &ExecuteOptions {
+                    force: true,
+                    ..ExecuteOptions::default()
+                }
+            ),
+            Err(MassMoveError::DuplicateDestination { .. })
+        ));
+        let files_count = fs::read_dir(directory_path).unwrap().count();
+        assert_eq!(files_count, 5);
+    }
+}
+
+#[cfg(test)]
+mod test_rename_chains {
+    use super::read_directory;
+    use mass_move_lib::execute::{execute, ExecuteOptions};
+    use mass_move_lib::mass_move::mass_move;
+    use mass_move_lib::rename_plan::{RenameEntry, RenamePlan};
+    use std::fs;
+    use tempfile::{self, TempDir};
+
+    #[test]
+    fn test_swap_by_patterns() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/x_a_b", directory_path), "ab").unwrap();
+        fs::write(format!("{}/x_b_a", directory_path), "ba").unwrap();
+        fs::write(format!("{}/x_c_d", directory_path), "cd").unwrap();
+
+        let res = mass_move(
+            &(directory_path.to_string() + "/x_*_*"),
+            &(directory_path.to_string() + "/x_#2_#1"),
+            false,
+        );
+        assert!(res.is_ok());
+        assert_eq!(
+            read_directory(directory_path),
+            vec![
+                ("x_a_b".to_string(), "ba".to_string()),
+                ("x_b_a".to_string(), "ab".to_string()),
+                ("x_d_c".to_string(), "cd".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shift_and_rotate() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        for number in 1..=3 {
+            fs::write(
+                format!("{}/file_{}", directory_path, number),
+                number.to_string(),
+            )
+            .unwrap();
+        }
+        let entry = |source: usize, destination: usize| RenameEntry {
+            source: format!("{}/file_{}", directory_path, source),
+            destination: format!("{}/file_{}", directory_path, destination),
+            captures: vec![source.to_string()],
+        };
+
+        let shift = RenamePlan {
+            entries: vec![entry(1, 2), entry(2, 3), entry(3, 4)],
+            ..RenamePlan::default()
+        };
+        assert!(shift.conflicts().is_empty());
+        assert!(execute(&shift, &ExecuteOptions::default()).is_ok());
+        assert_eq!(
+            read_directory(directory_path),
+            vec![
+                ("file_2".to_string(), "1".to_string()),
+                ("file_3".to_string(), "2".to_string()),
+                ("file_4".to_string(), "3".to_string()),
+            ]
+        );
+
+        let rotate = RenamePlan {
+            entries: vec![entry(2, 3), entry(3, 4), entry(4, 2)],
+            ..RenamePlan::default()
+        };
+        assert!(rotate.conflicts().is_empty());
+        assert!(execute(&rotate, &ExecuteOptions::default()).is_ok());
+        assert_eq!(
+            read_directory(directory_path),
+            vec![
+                ("file_2".to_string(), "3".to_string()),
+                ("file_3".to_string(), "1".to_string()),
+                ("file_4".to_string(), "2".to_string()),
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_transactional {
+    use super::read_directory;
+    use mass_move_lib::error::MassMoveError;
+    use mass_move_lib::execute::{execute, execute_with_progress, ExecuteOptions, Progress};
+    use mass_move_lib::rename_plan::{plan, RenameEntry};
+    use std::fs;
+    use tempfile::{self, TempDir};
+
+    #[test]
+    fn test_rollback() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a_1", directory_path), "a1").unwrap();
+        fs::write(format!("{}/a_2", directory_path), "a2").unwrap();
+        fs::write(format!("{}/b_2", directory_path), "b2").unwrap();
+        let files_before = read_directory(directory_path);
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/a_*"),
+            &(directory_path.to_string() + "/b_#1"),
+        )
+        .unwrap();
+        rename_plan.entries.push(RenameEntry {
+            source: format!("{}/not_exists", directory_path),
+            destination: format!("{}/b_3", directory_path),
+            captures: vec![],
+        });
+
+        let options = ExecuteOptions {
+            force: true,
+            transactional: true,
+            ..ExecuteOptions::default()
+        };
+        let error = execute(&rename_plan, &options).unwrap_err();
+        match error {
+            MassMoveError::RolledBack {
+                failure,
+                rollback_errors,
+            } => {
+                assert!(matches!(*failure, MassMoveError::Io { .. }));
+                assert!(rollback_errors.is_empty());
+            }
+            _ => panic!("unexpected error: {}", error),
+        }
+        assert_eq!(read_directory(directory_path), files_before);
+    }
+
+    #[test]
+    fn test_without_transaction() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a_1", directory_path), "a1").unwrap();
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/a_*"),
+            &(directory_path.to_string() + "/b_#1"),
+        )
+        .unwrap();
+        rename_plan.entries.push(RenameEntry {
+            source: format!("{}/not_exists", directory_path),
+            destination: format!("{}/b_3", directory_path),
+            captures: vec![],
+        });
+
+        let error = execute(&rename_plan, &ExecuteOptions::default()).unwrap_err();
+        assert!(matches!(error, MassMoveError::Io { .. }));
+        assert_eq!(
+            read_directory(directory_path),
+            vec![("b_1".to_string(), "a1".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_progress_of_failed_runs() {
+        for transactional in [false, true] {
+            let temporary_directory = TempDir::new().unwrap();
+            let directory_path = temporary_directory.path().to_str().unwrap();
+            fs::write(format!("{}/a_1", directory_path), "a1").unwrap();
+
+            let mut rename_plan = plan(
+                &(directory_path.to_string() + "/a_*"),
+                &(directory_path.to_string() + "/b_#1"),
+            )
+            .unwrap();
+            rename_plan.entries.push(RenameEntry {
+                source: format!("{}/not_exists", directory_path),
+                destination: format!("{}/b_3", directory_path),
+                captures: vec![],
+            });
+            let options = ExecuteOptions {
+                transactional,
+                ..ExecuteOptions::default()
+            };
+            let mut performed: Vec<String> = Vec::new();
+            let result = execute_with_progress(&rename_plan, &options, |progress| {
+                if let Progress::Performed { step, is_copied } = progress {
+                    assert!(!is_copied);
+                    performed.push(step.destination.clone());
+                }
+            });
+            assert!(result.is_err());
+            if transactional {
+                assert!(performed.is_empty());
+            } else {
+                assert_eq!(performed, vec![format!("{}/b_1", directory_path)]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_commit_removes_backups() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a_1", directory_path), "a1").unwrap();
+        fs::write(format!("{}/b_1", directory_path), "b1").unwrap();
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/a_*"),
+            &(directory_path.to_string() + "/b_#1"),
+        )
+        .unwrap();
+        let options = ExecuteOptions {
+            force: true,
+            transactional: true,
+            ..ExecuteOptions::default()
+        };
+        assert!(execute(&rename_plan, &options).is_ok());
+        assert_eq!(
+            read_directory(directory_path),
+            vec![("b_1".to_string(), "a1".to_string())]
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_undo {
+    use super::read_directory;
+    use mass_move_lib::execute::{execute, ExecuteOptions};
+    use mass_move_lib::journal::{read_journal, undo};
+    use mass_move_lib::rename_plan::plan;
+    use std::fs;
+    use tempfile::{self, TempDir};
+
+    #[test]
+    fn test_undo_swap() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        let journal_directory = TempDir::new().unwrap();
+        let journal_path = journal_directory.path().join("journal.jsonl");
+        fs::write(format!("{}/x_a_b", directory_path), "ab").unwrap();
+        fs::write(format!("{}/x_b_a", directory_path), "ba").unwrap();
+        fs::write(format!("{}/x_c_d", directory_path), "cd").unwrap();
+        let files_before = read_directory(directory_path);
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/x_*_*"),
+            &(directory_path.to_string() + "/x_#2_#1"),
+        )
+        .unwrap();
+        let options = ExecuteOptions {
+            journal: Some(journal_path.clone()),
+            ..ExecuteOptions::default()
+        };
+        assert!(execute(&rename_plan, &options).is_ok());
+        assert_ne!(read_directory(directory_path), files_before);
+        assert_eq!(read_journal(&journal_path).unwrap().len(), 4);
+
+        assert_eq!(undo(&journal_path).unwrap().len(), 4);
+        assert_eq!(read_directory(directory_path), files_before);
+    }
+
+    #[test]
+    fn test_undo_forced_transactional_run() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        let journal_directory = TempDir::new().unwrap();
+        let journal_path = journal_directory.path().join("journal.jsonl");
+        fs::write(format!("{}/a_1", directory_path), "new").unwrap();
+        fs::write(format!("{}/b_1", directory_path), "old").unwrap();
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/a_*"),
+            &(directory_path.to_string() + "/b_#1"),
+        )
+        .unwrap();
+        let options = ExecuteOptions {
+            force: true,
+            transactional: true,
+            journal: Some(journal_path.clone()),
+            ..ExecuteOptions::default()
+        };
+        execute(&rename_plan, &options).unwrap();
+        assert_eq!(
+            read_directory(directory_path),
+            vec![("b_1".to_string(), "new".to_string())]
+        );
+        assert_eq!(read_journal(&journal_path).unwrap().len(), 3);
+
+        let undone = undo(&journal_path).unwrap();
+        assert_eq!(undone.len(), 1);
+        assert_eq!(undone[0].destination, format!("{}/b_1", directory_path));
+        assert_eq!(
+            read_directory(directory_path),
+            vec![("a_1".to_string(), "new".to_string())]
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_copy {
+    use super::read_directory;
+    use mass_move_lib::error::MassMoveError;
+    use mass_move_lib::execute::{execute, ExecuteOptions};
+    use mass_move_lib::journal::undo;
+    use mass_move_lib::rename_plan::{plan, Conflict, Operation, RenameEntry};
+    use std::fs;
+    use tempfile::{self, TempDir};
+
+    #[test]
+    fn test_copy() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/some_A.bin", directory_path), "A").unwrap();
+        fs::write(format!("{}/some_B.bin", directory_path), "B").unwrap();
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/some_*.bin"),
+            &(directory_path.to_string() + "/changed_#1.bin"),
+        )
+        .unwrap();
+        rename_plan.operation = Operation::Copy;
+        assert!(execute(&rename_plan, &ExecuteOptions::default()).is_ok());
+        assert_eq!(
+            read_directory(directory_path),
+            vec![
+                ("changed_A.bin".to_string(), "A".to_string()),
+                ("changed_B.bin".to_string(), "B".to_string()),
+                ("some_A.bin".to_string(), "A".to_string()),
+                ("some_B.bin".to_string(), "B".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sources_are_not_vacated() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/x_a_b", directory_path), "ab").unwrap();
+        fs::write(format!("{}/x_b_a", directory_path), "ba").unwrap();
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/x_*_*"),
+            &(directory_path.to_string() + "/x_#2_#1"),
+        )
+        .unwrap();
+        assert!(rename_plan.conflicts().is_empty());
+        rename_plan.operation = Operation::Copy;
+        assert_eq!(rename_plan.conflicts().len(), 2);
+        assert!(execute(&rename_plan, &ExecuteOptions::default()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_forced_copy_over_links_to_sources() {
+        for is_symlink in [true, false] {
+            let temporary_directory = TempDir::new().unwrap();
+            let directory_path = temporary_directory.path().to_str().unwrap();
+            let source = format!("{}/a_1", directory_path);
+            let destination = format!("{}/d_1", directory_path);
+            fs::write(&source, "data").unwrap();
+            if is_symlink {
+                std::os::unix::fs::symlink(&source, &destination).unwrap();
+            } else {
+                fs::hard_link(&source, &destination).unwrap();
+            }
+
+            let mut rename_plan = plan(
+                &(directory_path.to_string() + "/a_*"),
+                &(directory_path.to_string() + "/d_#1"),
+            )
+            .unwrap();
+            rename_plan.operation = Operation::Copy;
+            let options = ExecuteOptions {
+                force: true,
+                ..ExecuteOptions::default()
+            };
+            execute(&rename_plan, &options).unwrap();
+            assert_eq!(fs::read_to_string(&source).unwrap(), "data");
+            assert_eq!(fs::read_to_string(&destination).unwrap(), "data");
+            assert!(fs::symlink_metadata(&destination).unwrap().is_file());
+
+            fs::write(&destination, "changed").unwrap();
+            assert_eq!(fs::read_to_string(&source).unwrap(), "data");
+        }
+    }
+
+    #[test]
+    fn test_forced_copy_along_chain_is_refused() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a", directory_path), "1").unwrap();
+        fs::write(format!("{}/aa", directory_path), "2").unwrap();
+        let files_before = read_directory(directory_path);
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/a*"),
+            &(directory_path.to_string() + "/aa#1"),
+        )
+        .unwrap();
+        rename_plan.operation = Operation::Copy;
+        assert_eq!(
+            rename_plan.conflicts(),
+            vec![Conflict::DestinationIsSource(format!(
+                "{}/aa",
+                directory_path
+            ))]
+        );
+        for transactional in [false, true] {
+            let options = ExecuteOptions {
+                force: true,
+                transactional,
+                ..ExecuteOptions::default()
+            };
+            assert!(matches!(
+                execute(&rename_plan, &options),
+                Err(MassMoveError::DestinationIsSource(_))
+            ));
+            assert_eq!(read_directory(directory_path), files_before);
+        }
+    }
+
+    #[test]
+    fn test_transactional_copy_and_undo() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        let journal_directory = TempDir::new().unwrap();
+        let journal_path = journal_directory.path().join("journal.jsonl");
+        fs::write(format!("{}/a_1", directory_path), "a1").unwrap();
+        fs::write(format!("{}/b_1", directory_path), "b1").unwrap();
+        let files_before = read_directory(directory_path);
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/a_*"),
+            &(directory_path.to_string() + "/b_#1"),
+        )
+        .unwrap();
+        rename_plan.operation = Operation::Copy;
+        rename_plan.entries.push(RenameEntry {
+            source: format!("{}/not_exists", directory_path),
+            destination: format!("{}/b_2", directory_path),
+            captures: vec![],
+        });
+        let options = ExecuteOptions {
+            force: true,
+            transactional: true,
+            ..ExecuteOptions::default()
+        };
+        assert!(execute(&rename_plan, &options).is_err());
+        assert_eq!(read_directory(directory_path), files_before);
+
+        rename_plan.entries.pop();
+        fs::remove_file(format!("{}/b_1", directory_path)).unwrap();
+        let options = ExecuteOptions {
+            journal: Some(journal_path.clone()),
+            ..ExecuteOptions::default()
+        };
+        assert!(execute(&rename_plan, &options).is_ok());
+        assert_eq!(read_directory(directory_path).len(), 2);
+        assert_eq!(undo(&journal_path).unwrap().len(), 1);
+        assert_eq!(
+            read_directory(directory_path),
+            vec![("a_1".to_string(), "a1".to_string())]
+        );
+    }
+}
+
+#[cfg(unix)]
+#[cfg(test)]
+mod test_links {
+    use super::read_directory;
+    use mass_move_lib::error::MassMoveError;
+    use mass_move_lib::execute::{execute, ExecuteOptions};
+    use mass_move_lib::journal::undo;
+    use mass_move_lib::rename_plan::{plan, Conflict, Operation, SymlinkStyle};
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+    use tempfile::{self, TempDir};
+
+    #[test]
+    fn test_hard_links() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a_1", directory_path), "a1").unwrap();
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/a_*"),
+            &(directory_path.to_string() + "/b_#1"),
+        )
+        .unwrap();
+        rename_plan.operation = Operation::HardLink;
+        assert!(execute(&rename_plan, &ExecuteOptions::default()).is_ok());
+        assert_eq!(
+            fs::metadata(format!("{}/a_1", directory_path))
+                .unwrap()
+                .ino(),
+            fs::metadata(format!("{}/b_1", directory_path))
+                .unwrap()
+                .ino()
+        );
+    }
+
+    #[test]
+    fn test_symlinks_and_undo() {
+        let data_directory = TempDir::new().unwrap();
+        let data_path = data_directory.path().to_str().unwrap();
+        let view_directory = TempDir::new().unwrap();
+        let view_path = view_directory.path().to_str().unwrap();
+        let journal_path = view_directory.path().join("journal.jsonl");
+        fs::write(format!("{}/photo_1.jpg", data_path), "1").unwrap();
+        fs::write(format!("{}/photo_2.jpg", data_path), "2").unwrap();
+
+        for (style, prefix) in [
+            (SymlinkStyle::Relative, "relative"),
+            (SymlinkStyle::Absolute, "absolute"),
+        ] {
+            let mut rename_plan = plan(
+                &(data_path.to_string() + "/photo_*.jpg"),
+                &format!("{}/{}_#1.jpg", view_path, prefix),
+            )
+            .unwrap();
+            rename_plan.operation = Operation::Symlink(style);
+            let options = ExecuteOptions {
+                journal: Some(journal_path.clone()),
+                ..ExecuteOptions::default()
+            };
+            assert!(execute(&rename_plan, &options).is_ok());
+        }
+
+        let relative_link = fs::read_link(format!("{}/relative_1.jpg", view_path)).unwrap();
+        assert!(relative_link.is_relative());
+        let absolute_link = fs::read_link(format!("{}/absolute_2.jpg", view_path)).unwrap();
+        assert!(absolute_link.is_absolute());
+        assert_eq!(
+            fs::read_to_string(format!("{}/relative_2.jpg", view_path)).unwrap(),
+            "2"
+        );
+
+        assert_eq!(undo(&journal_path).unwrap().len(), 4);
+        assert_eq!(fs::read_dir(view_path).unwrap().count(), 1);
+        assert_eq!(fs::read_dir(data_path).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_forced_links() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a_1", directory_path), "a1").unwrap();
+
+        for operation in [
+            Operation::HardLink,
+            Operation::Symlink(SymlinkStyle::Relative),
+        ] {
+            fs::write(format!("{}/b_1", directory_path), "old").unwrap();
+            let mut rename_plan = plan(
+                &(directory_path.to_string() + "/a_*"),
+                &(directory_path.to_string() + "/b_#1"),
+            )
+            .unwrap();
+            rename_plan.operation = operation;
+            assert!(execute(&rename_plan, &ExecuteOptions::default()).is_err());
+
+            let options = ExecuteOptions {
+                force: true,
+                ..ExecuteOptions::default()
+            };
+            execute(&rename_plan, &options).unwrap();
+            assert_eq!(
+                fs::metadata(format!("{}/a_1", directory_path))
+                    .unwrap()
+                    .ino(),
+                fs::metadata(format!("{}/b_1", directory_path))
+                    .unwrap()
+                    .ino()
+            );
+            fs::remove_file(format!("{}/b_1", directory_path)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_forced_links_along_chain_are_refused() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a", directory_path), "1").unwrap();
+        fs::write(format!("{}/aa", directory_path), "2").unwrap();
+        let files_before = read_directory(directory_path);
+
+        for operation in [
+            Operation::HardLink,
+            Operation::Symlink(SymlinkStyle::Relative),
+        ] {
+            let mut rename_plan = plan(
+                &(directory_path.to_string() + "/a*"),
+                &(directory_path.to_string() + "/aa#1"),
+            )
+            .unwrap();
+            rename_plan.operation = operation;
+            assert_eq!(
+                rename_plan.conflicts(),
+                vec![Conflict::DestinationIsSource(format!(
+                    "{}/aa",
+                    directory_path
+                ))]
+            );
+            for transactional in [false, true] {
+                let options = ExecuteOptions {
+                    force: true,
+                    transactional,
+                    ..ExecuteOptions::default()
+                };
+                assert!(matches!(
+                    execute(&rename_plan, &options),
+                    Err(MassMoveError::DestinationIsSource(_))
+                ));
+                assert_eq!(read_directory(directory_path), files_before);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_append {
+    use super::read_directory;
+    use mass_move_lib::execute::{execute, ExecuteOptions};
+    use mass_move_lib::journal::undo;
+    use mass_move_lib::rename_plan::{plan, Operation, RenameEntry};
+    use std::fs;
+    use tempfile::{self, TempDir};
+
+    #[test]
+    fn test_merge_shards_and_undo() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        let journal_directory = TempDir::new().unwrap();
+        let journal_path = journal_directory.path().join("journal.jsonl");
+        fs::write(format!("{}/log_1_part2.txt", directory_path), "1b;").unwrap();
+        fs::write(format!("{}/log_1_part1.txt", directory_path), "1a;").unwrap();
+        fs::write(format!("{}/log_2_part1.txt", directory_path), "2a;").unwrap();
+        fs::write(format!("{}/log_2.txt", directory_path), "old;").unwrap();
+        let files_before = read_directory(directory_path);
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/log_*_part*.txt"),
+            &(directory_path.to_string() + "/log_#1.txt"),
+        )
+        .unwrap();
+        rename_plan.operation = Operation::Append;
+        assert!(rename_plan.conflicts().is_empty());
+        let options = ExecuteOptions {
+            journal: Some(journal_path.clone()),
+            ..ExecuteOptions::default()
+        };
+        assert!(execute(&rename_plan, &options).is_ok());
+        assert_eq!(
+            fs::read_to_string(format!("{}/log_1.txt", directory_path)).unwrap(),
+            "1a;1b;"
+        );
+        assert_eq!(
+            fs::read_to_string(format!("{}/log_2.txt", directory_path)).unwrap(),
+            "old;2a;"
+        );
+
+        assert_eq!(undo(&journal_path).unwrap().len(), 3);
+        assert_eq!(read_directory(directory_path), files_before);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a_1", directory_path), "a1").unwrap();
+        fs::write(format!("{}/a_2", directory_path), "a2").unwrap();
+        fs::write(format!("{}/b", directory_path), "b").unwrap();
+        let files_before = read_directory(directory_path);
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/a_*"),
+            &(directory_path.to_string() + "/b"),
+        )
+        .unwrap();
+        rename_plan.operation = Operation::Append;
+        rename_plan.entries.push(RenameEntry {
+            source: format!("{}/a_1", directory_path),
+            destination: format!("{}/c", directory_path),
+            captures: vec![],
+        });
+        rename_plan.entries.push(RenameEntry {
+            source: format!("{}/not_exists", directory_path),
+            destination: format!("{}/b", directory_path),
+            captures: vec![],
+        });
+        let options = ExecuteOptions {
+            transactional: true,
+            ..ExecuteOptions::default()
+        };
+        assert!(execute(&rename_plan, &options).is_err());
+        assert_eq!(read_directory(directory_path), files_before);
+    }
+
+    #[test]
+    fn test_appending_to_source_is_conflict() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a_1", directory_path), "a1").unwrap();
+        fs::write(format!("{}/a_", directory_path), "a").unwrap();
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/a_*"),
+            &(directory_path.to_string() + "/a_"),
+        )
+        .unwrap();
+        rename_plan.operation = Operation::Append;
+        assert_eq!(rename_plan.conflicts().len(), 1);
+    }
+}
+
+#[cfg(test)]
+mod test_create_directories {
+    use super::read_directory;
+    use mass_move_lib::error::MassMoveError;
+    use mass_move_lib::execute::{execute, ExecuteOptions};
+    use mass_move_lib::rename_plan::{missing_directories, plan, RenameEntry};
+    use std::fs;
+    use tempfile::{self, TempDir};
+
+    #[test]
+    fn test_missing_directories_are_planned() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a.txt", directory_path), "a").unwrap();
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/*.txt"),
+            &(directory_path.to_string() + "/x/y/#1.txt"),
+        )
+        .unwrap();
+        assert_eq!(
+            rename_plan.directories,
+            vec![
+                format!("{}/x", directory_path),
+                format!("{}/x/y", directory_path)
+            ]
+        );
+
+        assert!(matches!(
+            execute(&rename_plan, &ExecuteOptions::default()),
+            Err(MassMoveError::Io { .. })
+        ));
+        let options = ExecuteOptions {
+            create_directories: true,
+            ..ExecuteOptions::default()
+        };
+        assert!(execute(&rename_plan, &options).is_ok());
+        assert_eq!(
+            read_directory(&format!("{}/x/y", directory_path)),
+            vec![("a.txt".to_string(), "a".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parent_directories_in_target() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a.txt", directory_path), "a").unwrap();
+        fs::write(format!("{}/b.txt", directory_path), "b").unwrap();
+        fs::create_dir(format!("{}/q", directory_path)).unwrap();
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/*.txt"),
+            &(directory_path.to_string() + "/q/../r/#1.txt"),
+        )
+        .unwrap();
+        rename_plan.entries[1].destination = format!("{}/s/../t/b.txt", directory_path);
+        rename_plan.directories = missing_directories(&rename_plan.entries);
+        assert_eq!(
+            rename_plan.directories,
+            vec![
+                format!("{}/q/../r", directory_path),
+                format!("{}/s", directory_path),
+                format!("{}/s/../t", directory_path)
+            ]
+        );
+
+        let options = ExecuteOptions {
+            create_directories: true,
+            ..ExecuteOptions::default()
+        };
+        execute(&rename_plan, &options).unwrap();
+        assert_eq!(read_directory(&format!("{}/r", directory_path)).len(), 1);
+        assert_eq!(read_directory(&format!("{}/t", directory_path)).len(), 1);
+    }
+
+    #[test]
+    fn test_placeholders_in_directories() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/IMG_2021_a.jpg", directory_path), "a").unwrap();
+        fs::write(format!("{}/IMG_2021_b.jpg", directory_path), "b").unwrap();
+        fs::write(format!("{}/IMG_2022_c.jpg", directory_path), "c").unwrap();
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/IMG_*_*.jpg"),
+            &(directory_path.to_string() + "/by_year/#1/#2.jpg"),
+        )
+        .unwrap();
+        assert_eq!(
+            rename_plan.entries[2].destination,
+            format!("{}/by_year/2022/c.jpg", directory_path)
+        );
+        assert_eq!(
+            rename_plan.directories,
+            vec![
+                format!("{}/by_year", directory_path),
+                format!("{}/by_year/2021", directory_path),
+                format!("{}/by_year/2022", directory_path),
+            ]
+        );
+
+        let options = ExecuteOptions {
+            create_directories: true,
+            ..ExecuteOptions::default()
+        };
+        assert!(execute(&rename_plan, &options).is_ok());
+        assert_eq!(
+            read_directory(&format!("{}/by_year/2021", directory_path)),
+            vec![
+                ("a.jpg".to_string(), "a".to_string()),
+                ("b.jpg".to_string(), "b".to_string())
+            ]
+        );
+        assert_eq!(
+            read_directory(&format!("{}/by_year/2022", directory_path)),
+            vec![("c.jpg".to_string(), "c".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_recursive_backup() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::create_dir_all(format!("{}/src/bin", directory_path)).unwrap();
+        fs::write(format!("{}/src/lib.rs", directory_path), "lib").unwrap();
+        fs::write(format!("{}/src/bin/main.rs", directory_path), "main").unwrap();
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/src/**/*.rs"),
+            &(directory_path.to_string() + "/backup/#1/#2.rs.bak"),
+        )
+        .unwrap();
+        assert_eq!(rename_plan.entries[0].captures, vec!["", "lib"]);
+        assert_eq!(rename_plan.entries[1].captures, vec!["bin", "main"]);
+
+        let options = ExecuteOptions {
+            create_directories: true,
+            ..ExecuteOptions::default()
+        };
+        assert!(execute(&rename_plan, &options).is_ok());
+        assert_eq!(
+            fs::read_to_string(format!("{}/backup/lib.rs.bak", directory_path)).unwrap(),
+            "lib"
+        );
+        assert_eq!(
+            read_directory(&format!("{}/backup/bin", directory_path)),
+            vec![("main.rs.bak".to_string(), "main".to_string())]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_directory_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a.txt", directory_path), "a").unwrap();
+
+        let rename_plan = plan(
+            &(directory_path.to_string() + "/*.txt"),
+            &(directory_path.to_string() + "/x/#1.txt"),
+        )
+        .unwrap();
+        let options = ExecuteOptions {
+            create_directories: true,
+            directory_mode: Some(0o750),
+            ..ExecuteOptions::default()
+        };
+        assert!(execute(&rename_plan, &options).is_ok());
+        assert_eq!(
+            fs::metadata(format!("{}/x", directory_path))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o7777,
+            0o750
+        );
+    }
+
+    #[test]
+    fn test_rollback_removes_directories() {
+        let temporary_directory = TempDir::new().unwrap();
+        let directory_path = temporary_directory.path().to_str().unwrap();
+        fs::write(format!("{}/a.txt", directory_path), "a").unwrap();
+        let files_before = read_directory(directory_path);
+
+        let mut rename_plan = plan(
+            &(directory_path.to_string() + "/*.txt"),
+            &(directory_path.to_string() + "/x/y/#1.txt"),
+        )
+        .unwrap();
+        rename_plan.entries.push(RenameEntry {
+            source: format!("{}/not_exists", directory_path),
+            destination: format!("{}/x/b.txt", directory_path),
+            captures: vec![],
+        });
+        let options = ExecuteOptions {
+            transactional: true,
+            create_directories: true,
+            ..ExecuteOptions::default()
+        };
+        assert!(matches!(
+            execute(&rename_plan, &options),
+            Err(MassMoveError::RolledBack { rollback_errors, .. }) if rollback_errors.is_empty()
+        ));
+        assert_eq!(read_directory(directory_path), files_before);
+    }
+}